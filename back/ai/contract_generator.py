@@ -21,14 +21,18 @@ def get_template_for_contract_type(contract_type):
     templates = {
         "escrow": """
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke, system_instruction};
-use std::str::FromStr;
+use anchor_lang::solana_program::{program::{invoke, invoke_signed}, system_instruction};
 
 declare_id!("11111111111111111111111111111111");
 
 // Registry integration code
-pub const REGISTRY_PROGRAM_ID: &str = "BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn";
+pub const REGISTRY_PROGRAM_ID: Pubkey = pubkey!("BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn");
 pub const REGISTRY_TRANSACTION_SEED: &str = "transaction_v1";
+// Seed of this program's PDA that signs registry CPIs to prove the caller
+pub const REGISTRY_CALLER_SEED: &[u8] = b"registry_caller";
+// Anchor discriminator for the registry's register_transaction instruction
+// (first 8 bytes of sha256("global:register_transaction"))
+pub const REGISTER_TRANSACTION_DISCRIMINATOR: [u8; 8] = [158, 135, 86, 133, 215, 10, 181, 168];
 
 // Structure for Registry transaction data
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -76,34 +80,43 @@ pub mod escrow {
             ],
         )?;
         
-        // Register the transaction with the registry program if provided
-        if ctx.accounts.registry_program.key() == Pubkey::from_str(REGISTRY_PROGRAM_ID).unwrap() {
-            let registry_data = RegistryTransactionData {
-                tx_type: "escrow_initialize".to_string(),
-                amount,
-                initiator: ctx.accounts.sender.key(),
-                target_account: ctx.accounts.receiver.key(),
-                description: format!("Escrow initialized with amount {}", amount),
-            };
-            
-            // Register the transaction using the helper function
-            register_transaction_helper(
-                ctx.accounts.registry_program.to_account_info(),
-                ctx.accounts.registry_transaction.to_account_info(),
-                ctx.accounts.sender.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                registry_data,
-            )?;
-        }
+        // Register the transaction with the registry program
+        let registry_data = RegistryTransactionData {
+            tx_type: "escrow_initialize".to_string(),
+            amount,
+            initiator: ctx.accounts.sender.key(),
+            target_account: ctx.accounts.receiver.key(),
+            description: format!("Escrow initialized with amount {}", amount),
+        };
+        
+        // Register the transaction using the helper function
+        register_transaction_helper(
+            ctx.accounts.registry_program.to_account_info(),
+            ctx.accounts.registry_authority.to_account_info(),
+            ctx.accounts.registry_counter.to_account_info(),
+            ctx.accounts.registry_transaction.to_account_info(),
+            ctx.accounts.registry_initiator_stats.to_account_info(),
+            ctx.accounts.registry_config.to_account_info(),
+            ctx.accounts.registry_treasury.to_account_info(),
+            ctx.accounts.sender.to_account_info(),
+            ctx.accounts.caller_program.to_account_info(),
+            ctx.accounts.registry_caller.to_account_info(),
+            ctx.bumps.registry_caller,
+            ctx.accounts.system_program.to_account_info(),
+            registry_data,
+        )?;
+        
+        emit!(EscrowCreatedEvent {
+            escrow: ctx.accounts.escrow_account.key(),
+            sender: ctx.accounts.sender.key(),
+            receiver: ctx.accounts.receiver.key(),
+            amount,
+        });
         
         Ok(())
     }
 
     pub fn release(ctx: Context<Release>) -> Result<()> {
-        // Get the escrow account info and balance first
-        let escrow_info = ctx.accounts.escrow_account.to_account_info();
-        let escrow_balance = escrow_info.lamports();
-        
         // Only the escrow authority can release funds
         require!(
             ctx.accounts.escrow_authority.key() == ctx.accounts.escrow_account.escrow_authority,
@@ -119,8 +132,14 @@ pub mod escrow {
         // Calculate the rent-exempt amount first
         let rent_exempt_lamports = rent.minimum_balance(8 + EscrowAccount::SIZE);
         
+        // Mark escrow as completed; Anchor serialises the flag to the account
+        // when the instruction exits, after the payout and registry CPI
+        ctx.accounts.escrow_account.is_completed = true;
+        
         // Calculate the amount to transfer (total balance minus rent-exempt amount)
-        let transfer_amount = escrow_balance
+        let escrow_info = ctx.accounts.escrow_account.to_account_info();
+        let transfer_amount = escrow_info
+            .lamports()
             .checked_sub(rent_exempt_lamports)
             .ok_or(EscrowError::MathOverflow)?;
         
@@ -134,38 +153,73 @@ pub mod escrow {
             .checked_add(transfer_amount)
             .ok_or(EscrowError::MathOverflow)?;
         
-        // Mark escrow as completed
-        ctx.accounts.escrow_account.is_completed = true;
+        // Register the transaction with the registry program
+        let registry_data = RegistryTransactionData {
+            tx_type: "escrow_release".to_string(),
+            amount: transfer_amount,
+            initiator: ctx.accounts.escrow_authority.key(),
+            target_account: ctx.accounts.receiver.key(),
+            description: format!("Escrow released with amount {}", transfer_amount),
+        };
         
-        // Register the transaction with the registry program if provided
-        if ctx.accounts.registry_program.key() == Pubkey::from_str(REGISTRY_PROGRAM_ID).unwrap() {
-            let registry_data = RegistryTransactionData {
-                tx_type: "escrow_release".to_string(),
-                amount: transfer_amount,
-                initiator: ctx.accounts.escrow_authority.key(),
-                target_account: ctx.accounts.receiver.key(),
-                description: format!("Escrow released with amount {}", transfer_amount),
-            };
-            
-            // Register the transaction using the helper function
-            register_transaction_helper(
-                ctx.accounts.registry_program.to_account_info(),
-                ctx.accounts.registry_transaction.to_account_info(),
-                ctx.accounts.escrow_authority.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                registry_data,
-            )?;
-        }
+        // Register the transaction using the helper function
+        register_transaction_helper(
+            ctx.accounts.registry_program.to_account_info(),
+            ctx.accounts.registry_authority.to_account_info(),
+            ctx.accounts.registry_counter.to_account_info(),
+            ctx.accounts.registry_transaction.to_account_info(),
+            ctx.accounts.registry_initiator_stats.to_account_info(),
+            ctx.accounts.registry_config.to_account_info(),
+            ctx.accounts.registry_treasury.to_account_info(),
+            ctx.accounts.escrow_authority.to_account_info(),
+            ctx.accounts.caller_program.to_account_info(),
+            ctx.accounts.registry_caller.to_account_info(),
+            ctx.bumps.registry_caller,
+            ctx.accounts.system_program.to_account_info(),
+            registry_data,
+        )?;
+        
+        emit!(EscrowReleasedEvent {
+            escrow: ctx.accounts.escrow_account.key(),
+            sender: ctx.accounts.escrow_account.sender,
+            receiver: ctx.accounts.receiver.key(),
+            amount: transfer_amount,
+        });
+        
+        Ok(())
+    }
+
+    pub fn close(ctx: Context<Close>) -> Result<()> {
+        // Completion and ownership are enforced by the Close constraints;
+        // the close attribute returns the rent to the sender on exit
+        let escrow_info = ctx.accounts.escrow_account.to_account_info();
+        
+        emit!(EscrowClosedEvent {
+            escrow: escrow_info.key(),
+            sender: ctx.accounts.sender.key(),
+            rent_returned: escrow_info.lamports(),
+        });
+        
+        msg!("Escrow account closed");
         
         Ok(())
     }
 
     pub fn register_transaction(ctx: Context<RegisterTransaction>, data: RegistryTransactionData) -> Result<()> {
-        // Register the transaction using the helper function
+        // Register the transaction using the helper function, signing as
+        // this program's registry caller PDA
         register_transaction_helper(
             ctx.accounts.registry_program.to_account_info(),
+            ctx.accounts.registry_authority.to_account_info(),
+            ctx.accounts.registry_counter.to_account_info(),
             ctx.accounts.registry_transaction.to_account_info(),
+            ctx.accounts.registry_initiator_stats.to_account_info(),
+            ctx.accounts.registry_config.to_account_info(),
+            ctx.accounts.registry_treasury.to_account_info(),
             ctx.accounts.payer.to_account_info(),
+            ctx.accounts.caller_program.to_account_info(),
+            ctx.accounts.registry_caller.to_account_info(),
+            ctx.bumps.registry_caller,
             ctx.accounts.system_program.to_account_info(),
             data,
         )
@@ -173,42 +227,64 @@ pub mod escrow {
 }
 
 // Helper function to register transactions with the registry program
+#[allow(clippy::too_many_arguments)]
 fn register_transaction_helper<'a>(
     registry_program: AccountInfo<'a>,
+    registry_authority: AccountInfo<'a>,
+    registry_counter: AccountInfo<'a>,
     registry_transaction: AccountInfo<'a>,
+    registry_initiator_stats: AccountInfo<'a>,
+    registry_config: AccountInfo<'a>,
+    registry_treasury: AccountInfo<'a>,
     payer: AccountInfo<'a>,
+    caller_program: AccountInfo<'a>,
+    registry_caller: AccountInfo<'a>,
+    registry_caller_bump: u8,
     system_program: AccountInfo<'a>,
     data: RegistryTransactionData,
 ) -> Result<()> {
-    // Serialize the transaction data
-    let mut tx_data = Vec::new();
-    data.serialize(&mut tx_data).map_err(|_| EscrowError::SerializationError)?;
-    
-    // Create cross-program invocation instruction data
-    let mut instruction_data = Vec::new();
-    instruction_data.push(0); // Instruction index for register_transaction
-    instruction_data.extend_from_slice(&tx_data);
+    // Build instruction data: Anchor discriminator followed by the Borsh encoded
+    // arguments, which share their field order with RegistryTransactionData
+    let mut instruction_data = REGISTER_TRANSACTION_DISCRIMINATOR.to_vec();
+    data.serialize(&mut instruction_data).map_err(|_| EscrowError::SerializationError)?;
     
-    // Create the instruction
+    // Accounts must follow the order of the registry's RegisterTransaction context
     let ix = anchor_lang::solana_program::instruction::Instruction {
         program_id: registry_program.key(),
         accounts: vec![
-            anchor_lang::solana_program::instruction::AccountMeta::new(registry_transaction.key(), false),
             anchor_lang::solana_program::instruction::AccountMeta::new(payer.key(), true),
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(caller_program.key(), false),
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(registry_caller.key(), true),
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(registry_authority.key(), false),
+            anchor_lang::solana_program::instruction::AccountMeta::new(registry_counter.key(), false),
+            anchor_lang::solana_program::instruction::AccountMeta::new(registry_transaction.key(), false),
+            anchor_lang::solana_program::instruction::AccountMeta::new(registry_initiator_stats.key(), false),
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(registry_config.key(), false),
+            anchor_lang::solana_program::instruction::AccountMeta::new(registry_treasury.key(), false),
             anchor_lang::solana_program::instruction::AccountMeta::new_readonly(system_program.key(), false),
         ],
         data: instruction_data,
     };
     
-    // Invoke the instruction
-    anchor_lang::solana_program::program::invoke(
+    // Sign as this program's registry caller PDA; any failure aborts the
+    // calling instruction
+    invoke_signed(
         &ix,
         &[
-            registry_transaction.clone(),
-            payer.clone(),
-            system_program.clone(),
+            payer,
+            caller_program,
+            registry_caller,
+            registry_authority,
+            registry_counter,
+            registry_transaction,
+            registry_initiator_stats,
+            registry_config,
+            registry_treasury,
+            system_program,
+            registry_program,
         ],
-    ).map_err(|_| EscrowError::RegistryError)?;
+        &[&[REGISTRY_CALLER_SEED, &[registry_caller_bump]]],
+    )?;
     
     Ok(())
 }
@@ -228,13 +304,31 @@ pub struct Initialize<'info> {
     )]
     pub escrow_account: Account<'info, EscrowAccount>,
     pub system_program: Program<'info, System>,
-    /// CHECK: Registry program will verify on its end
-    #[account(
-        constraint = registry_program.key() == Pubkey::from_str(REGISTRY_PROGRAM_ID).unwrap() @ EscrowError::InvalidRegistryProgram
-    )]
+    /// CHECK: Registry program address is verified against REGISTRY_PROGRAM_ID
+    #[account(address = REGISTRY_PROGRAM_ID @ EscrowError::InvalidRegistryProgram)]
     pub registry_program: AccountInfo<'info>,
-    /// CHECK: PDA will be handled by the Registry program
+    /// CHECK: Registry Authority PDA holding the allowed caller programs
+    pub registry_authority: AccountInfo<'info>,
+    /// CHECK: Per-payer TransactionCounter PDA, managed by the Registry program
+    #[account(mut)]
+    pub registry_counter: AccountInfo<'info>,
+    /// CHECK: TransactionRecord PDA, initialised and verified by the Registry program
+    #[account(mut)]
     pub registry_transaction: AccountInfo<'info>,
+    /// CHECK: Per-initiator InitiatorStats PDA, managed by the Registry program
+    #[account(mut)]
+    pub registry_initiator_stats: AccountInfo<'info>,
+    /// CHECK: Registry Config PDA holding the registration fee and treasury
+    pub registry_config: AccountInfo<'info>,
+    /// CHECK: Fee treasury, verified against the config by the Registry program
+    #[account(mut)]
+    pub registry_treasury: AccountInfo<'info>,
+    /// CHECK: This program, recorded by the registry as the caller
+    #[account(address = crate::ID)]
+    pub caller_program: AccountInfo<'info>,
+    /// CHECK: PDA that signs the registry CPI on behalf of this program
+    #[account(seeds = [REGISTRY_CALLER_SEED], bump)]
+    pub registry_caller: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -251,23 +345,75 @@ pub struct Release<'info> {
     )]
     pub receiver: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
-    /// CHECK: Registry program will verify on its end
-    #[account(
-        constraint = registry_program.key() == Pubkey::from_str(REGISTRY_PROGRAM_ID).unwrap() @ EscrowError::InvalidRegistryProgram
-    )]
+    /// CHECK: Registry program address is verified against REGISTRY_PROGRAM_ID
+    #[account(address = REGISTRY_PROGRAM_ID @ EscrowError::InvalidRegistryProgram)]
     pub registry_program: AccountInfo<'info>,
-    /// CHECK: PDA will be handled by the Registry program
+    /// CHECK: Registry Authority PDA holding the allowed caller programs
+    pub registry_authority: AccountInfo<'info>,
+    /// CHECK: Per-payer TransactionCounter PDA, managed by the Registry program
+    #[account(mut)]
+    pub registry_counter: AccountInfo<'info>,
+    /// CHECK: TransactionRecord PDA, initialised and verified by the Registry program
+    #[account(mut)]
     pub registry_transaction: AccountInfo<'info>,
+    /// CHECK: Per-initiator InitiatorStats PDA, managed by the Registry program
+    #[account(mut)]
+    pub registry_initiator_stats: AccountInfo<'info>,
+    /// CHECK: Registry Config PDA holding the registration fee and treasury
+    pub registry_config: AccountInfo<'info>,
+    /// CHECK: Fee treasury, verified against the config by the Registry program
+    #[account(mut)]
+    pub registry_treasury: AccountInfo<'info>,
+    /// CHECK: This program, recorded by the registry as the caller
+    #[account(address = crate::ID)]
+    pub caller_program: AccountInfo<'info>,
+    /// CHECK: PDA that signs the registry CPI on behalf of this program
+    #[account(seeds = [REGISTRY_CALLER_SEED], bump)]
+    pub registry_caller: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    #[account(
+        mut,
+        has_one = sender @ EscrowError::UnauthorizedAccess,
+        constraint = escrow_account.is_completed @ EscrowError::EscrowStillActive,
+        close = sender
+    )]
+    pub escrow_account: Account<'info, EscrowAccount>,
 }
 
 #[derive(Accounts)]
 pub struct RegisterTransaction<'info> {
-    /// CHECK: The registry program to call
+    /// CHECK: Registry program address is verified against REGISTRY_PROGRAM_ID
+    #[account(address = REGISTRY_PROGRAM_ID @ EscrowError::InvalidRegistryProgram)]
     pub registry_program: AccountInfo<'info>,
-    /// CHECK: The registry transaction account
+    /// CHECK: Registry Authority PDA holding the allowed caller programs
+    pub registry_authority: AccountInfo<'info>,
+    /// CHECK: Per-payer TransactionCounter PDA, managed by the Registry program
+    #[account(mut)]
+    pub registry_counter: AccountInfo<'info>,
+    /// CHECK: TransactionRecord PDA, initialised and verified by the Registry program
+    #[account(mut)]
     pub registry_transaction: AccountInfo<'info>,
+    /// CHECK: Per-initiator InitiatorStats PDA, managed by the Registry program
+    #[account(mut)]
+    pub registry_initiator_stats: AccountInfo<'info>,
+    /// CHECK: Registry Config PDA holding the registration fee and treasury
+    pub registry_config: AccountInfo<'info>,
+    /// CHECK: Fee treasury, verified against the config by the Registry program
+    #[account(mut)]
+    pub registry_treasury: AccountInfo<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    /// CHECK: This program, recorded by the registry as the caller
+    #[account(address = crate::ID)]
+    pub caller_program: AccountInfo<'info>,
+    /// CHECK: PDA that signs the registry CPI on behalf of this program
+    #[account(seeds = [REGISTRY_CALLER_SEED], bump)]
+    pub registry_caller: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -300,12 +446,40 @@ pub enum EscrowError {
     MathOverflow,
     #[msg("Serialization error")]
     SerializationError,
+    // No longer raised, since registry CPI errors now propagate as-is; kept
+    // so the codes of the variants after it do not change
     #[msg("Registry program invocation error")]
     RegistryError,
     #[msg("Invalid registry program address")]
     InvalidRegistryProgram,
     #[msg("Invalid receiver")]
     InvalidReceiver,
+    #[msg("The escrow must be completed before it can be closed")]
+    EscrowStillActive,
+}
+
+// Events
+#[event]
+pub struct EscrowCreatedEvent {
+    pub escrow: Pubkey,
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EscrowReleasedEvent {
+    pub escrow: Pubkey,
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EscrowClosedEvent {
+    pub escrow: Pubkey,
+    pub sender: Pubkey,
+    pub rent_returned: u64,
 }
 """,
         "crowdfunding": """
@@ -593,7 +767,9 @@ def generate_smart_contract(contract_type, schema, output_path=None):
     Interoperate with Registry programme (ID: {REGISTRY_PROGRAM_ID})
     
     Requirements:
-    1. Include registry programme and transaction accounts
+    1. Include the registry programme, authority, counter, transaction, initiator stats,
+       config and treasury accounts, and sign the CPI with the programme's
+       [b"registry_caller"] PDA (see back/templates/registry_interface.rs)
     2. Use proper error handling for registry operations
     3. Implement proper serialisation for registry data
     4. Use helper functions to avoid code duplication
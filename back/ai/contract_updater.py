@@ -62,8 +62,12 @@ def update_contract(contract_type, contract_code, update_requirements, output_pa
         # Prepare registry integration code if not already present
         registry_interface_code = f"""
 // Registry integration code
-pub const REGISTRY_PROGRAM_ID: &str = "BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn";
-pub const REGISTRY_TRANSACTION_SEED: &str = "transaction_v1";
+pub const REGISTRY_PROGRAM_ID: Pubkey = pubkey!("BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn");
+// Seed of this program's PDA that signs registry CPIs to prove the caller
+pub const REGISTRY_CALLER_SEED: &[u8] = b"registry_caller";
+// Anchor discriminator for the registry's register_transaction instruction
+// (first 8 bytes of sha256("global:register_transaction"))
+pub const REGISTER_TRANSACTION_DISCRIMINATOR: [u8; 8] = [158, 135, 86, 133, 215, 10, 181, 168];
 
 // Structure for Registry transaction data
 #[derive(AnchorSerialize)]
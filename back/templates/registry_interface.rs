@@ -1,11 +1,118 @@
 use anchor_lang::prelude::*;
-use std::str::FromStr;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
 
 // Registry Interface Code for Anchor Programs
 // This interface allows any Anchor program to register transactions with
-// the central Registry program on Solana devnet for tracking purposes
+// the central Registry program on Solana devnet for tracking purposes.
+//
+// The calling program must be on the registry's allowed caller list and
+// signs every registration with its own [REGISTRY_CALLER_SEED] PDA. Its
+// instruction contexts need these accounts alongside the payer:
+//
+//     /// CHECK: Registry program address is verified against REGISTRY_PROGRAM_ID
+//     #[account(address = REGISTRY_PROGRAM_ID)]
+//     pub registry_program: AccountInfo<'info>,
+//     /// CHECK: Registry Authority PDA, seeds [b"authority"]
+//     pub registry_authority: AccountInfo<'info>,
+//     /// CHECK: Per-payer TransactionCounter PDA, seeds [b"counter", payer]
+//     #[account(mut)]
+//     pub registry_counter: AccountInfo<'info>,
+//     /// CHECK: TransactionRecord PDA, seeds [b"transaction_v1", payer, count as u64 LE]
+//     #[account(mut)]
+//     pub registry_transaction: AccountInfo<'info>,
+//     /// CHECK: Per-initiator InitiatorStats PDA, seeds [b"stats", initiator]
+//     #[account(mut)]
+//     pub registry_initiator_stats: AccountInfo<'info>,
+//     /// CHECK: Registry Config PDA, seeds [b"config"]
+//     pub registry_config: AccountInfo<'info>,
+//     /// CHECK: Fee treasury stored on the registry config
+//     #[account(mut)]
+//     pub registry_treasury: AccountInfo<'info>,
+//     /// CHECK: This program, recorded by the registry as the caller
+//     #[account(address = crate::ID)]
+//     pub caller_program: AccountInfo<'info>,
+//     /// CHECK: PDA that signs the registry CPI on behalf of this program
+//     #[account(seeds = [REGISTRY_CALLER_SEED], bump)]
+//     pub registry_caller: AccountInfo<'info>,
+//     pub system_program: Program<'info, System>,
 
-pub const REGISTRY_PROGRAM_ID: &str = "BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn";
-pub const REGISTRY_TRANSACTION_SEED: &str = "transaction_v1";
+pub const REGISTRY_PROGRAM_ID: Pubkey = pubkey!("BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn");
+pub const REGISTRY_CALLER_SEED: &[u8] = b"registry_caller";
+// Anchor discriminator for the registry's register_transaction instruction
+// (first 8 bytes of sha256("global:register_transaction"))
+pub const REGISTER_TRANSACTION_DISCRIMINATOR: [u8; 8] = [158, 135, 86, 133, 215, 10, 181, 168];
 
-// Rest of file unchanged... 
\ No newline at end of file
+// Structure for Registry transaction data
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RegistryTransactionData {
+    pub tx_type: String,         // Max 50 characters
+    pub amount: u64,
+    pub initiator: Pubkey,
+    pub target_account: Pubkey,
+    pub description: String,     // Max 100 characters
+}
+
+// Registers a transaction with the registry, signing as this program's
+// registry caller PDA
+#[allow(clippy::too_many_arguments)]
+pub fn register_transaction<'a>(
+    registry_program: AccountInfo<'a>,
+    registry_authority: AccountInfo<'a>,
+    registry_counter: AccountInfo<'a>,
+    registry_transaction: AccountInfo<'a>,
+    registry_initiator_stats: AccountInfo<'a>,
+    registry_config: AccountInfo<'a>,
+    registry_treasury: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    caller_program: AccountInfo<'a>,
+    registry_caller: AccountInfo<'a>,
+    registry_caller_bump: u8,
+    system_program: AccountInfo<'a>,
+    data: RegistryTransactionData,
+) -> Result<()> {
+    // Anchor discriminator followed by the Borsh encoded arguments
+    let mut instruction_data = REGISTER_TRANSACTION_DISCRIMINATOR.to_vec();
+    data.serialize(&mut instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    // Accounts must follow the order of the registry's RegisterTransaction context
+    let ix = Instruction {
+        program_id: registry_program.key(),
+        accounts: vec![
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(caller_program.key(), false),
+            AccountMeta::new_readonly(registry_caller.key(), true),
+            AccountMeta::new_readonly(registry_authority.key(), false),
+            AccountMeta::new(registry_counter.key(), false),
+            AccountMeta::new(registry_transaction.key(), false),
+            AccountMeta::new(registry_initiator_stats.key(), false),
+            AccountMeta::new_readonly(registry_config.key(), false),
+            AccountMeta::new(registry_treasury.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+        ],
+        data: instruction_data,
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            payer,
+            caller_program,
+            registry_caller,
+            registry_authority,
+            registry_counter,
+            registry_transaction,
+            registry_initiator_stats,
+            registry_config,
+            registry_treasury,
+            system_program,
+            registry_program,
+        ],
+        &[&[REGISTRY_CALLER_SEED, &[registry_caller_bump]]],
+    )?;
+
+    Ok(())
+}
@@ -125,20 +125,29 @@ async function executeSimpleTransaction(connection, wallet) {
 }
 
 /**
- * Calculate a PDA for the registry transaction
+ * Calculate the PDA of the next registry transaction recorded for a payer
  */
-async function findRegistryTransactionPDA(payer, txType, amount) {
-  // Convert amount to LE bytes
-  const amountLeBytes = new BN(amount).toBuffer('le', 8);
+async function findRegistryTransactionPDA(connection, payer) {
+  const registryProgramId = new PublicKey(args.registryProgramId);
   
-  return await PublicKey.findProgramAddressSync(
+  // Records are keyed by the payer's TransactionCounter, not the tx contents
+  const [counterPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("counter"), payer.toBuffer()],
+    registryProgramId
+  );
+  const counterInfo = await connection.getAccountInfo(counterPda);
+  // Skip the 8-byte discriminator to read the u64 count
+  const sequence = counterInfo
+    ? counterInfo.data.slice(8, 16)
+    : new BN(0).toBuffer('le', 8);
+  
+  return PublicKey.findProgramAddressSync(
     [
       Buffer.from("transaction_v1"),
       payer.toBuffer(),
-      Buffer.from(txType),
-      amountLeBytes
+      sequence
     ],
-    new PublicKey(args.registryProgramId)
+    registryProgramId
   );
 }
 
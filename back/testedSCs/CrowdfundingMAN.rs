@@ -13,11 +13,13 @@ pub mod crowdfunding {
         name: String, 
         description: String,
         target_amount: u64,
-        deadline: i64
+        deadline: i64,
+        cap_at_goal: bool
     ) -> Result<()> {
-        if deadline < Clock::get().unwrap().unix_timestamp {
-            return Err(CampaignError::DeadlineShouldBeInFuture.into());
-        }
+        require!(
+            deadline >= Clock::get()?.unix_timestamp,
+            CampaignError::DeadlineShouldBeInFuture
+        );
 
         let campaign = &mut ctx.accounts.campaign;
         campaign.admin = ctx.accounts.admin.key();
@@ -27,6 +29,8 @@ pub mod crowdfunding {
         campaign.deadline = deadline;
         campaign.amount_raised = 0;
         campaign.closed = false;
+        campaign.cap_at_goal = cap_at_goal;
+        campaign.cancelled = false;
         
         Ok(())
     }
@@ -34,15 +38,21 @@ pub mod crowdfunding {
     /// Processes a donation to the campaign
     pub fn donate(ctx: Context<Donate>, amount: u64) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
+        let donation = &mut ctx.accounts.donation;
         let donator = &ctx.accounts.donator;
         
-        if campaign.closed {
-            return Err(CampaignError::CampaignNotActive.into());
-        }
-        
-        if Clock::get().unwrap().unix_timestamp >= campaign.deadline {
-            return Err(CampaignError::CampaignEnded.into());
-        }
+        require!(!campaign.closed, CampaignError::CampaignNotActive);
+        require!(
+            Clock::get()?.unix_timestamp < campaign.deadline,
+            CampaignError::CampaignEnded
+        );
+
+        let new_amount_raised = next_amount_raised(
+            campaign.amount_raised,
+            amount,
+            campaign.target_amount,
+            campaign.cap_at_goal,
+        )?;
 
         invoke(
             &system_instruction::transfer(
@@ -57,7 +67,14 @@ pub mod crowdfunding {
             ],
         )?;
 
-        campaign.amount_raised += amount;
+        campaign.amount_raised = new_amount_raised;
+
+        // Track each donor's total so it can be refunded on cancellation
+        donation.donator = donator.key();
+        donation.bump = ctx.bumps.donation;
+        donation.amount = donation.amount
+            .checked_add(amount)
+            .ok_or(CampaignError::AmountOverflow)?;
 
         Ok(())
     }
@@ -67,19 +84,21 @@ pub mod crowdfunding {
         let campaign = &mut ctx.accounts.campaign;
         let admin = &ctx.accounts.admin;
 
-        if Clock::get().unwrap().unix_timestamp < campaign.deadline {
-            return Err(CampaignError::DeadlineNotReached.into());
-        }
-
-        if campaign.closed {
-            return Err(CampaignError::CampaignNotActive.into());
-        }
+        // Close the campaign before any lamports move
+        close_for_withdraw(campaign, Clock::get()?.unix_timestamp)?;
 
-        let campaign_key = campaign.key();
-        **campaign.to_account_info().try_borrow_mut_lamports()? = 0;
-        **admin.to_account_info().try_borrow_mut_lamports()? += campaign.to_account_info().lamports();
+        // Capture the balance before touching lamports, keeping the account rent-exempt
+        let campaign_info = campaign.to_account_info();
+        let admin_info = admin.to_account_info();
+        let rent_exempt_lamports = Rent::get()?.minimum_balance(campaign_info.data_len());
+        let admin_balance = withdraw_payout(
+            campaign_info.lamports(),
+            rent_exempt_lamports,
+            admin_info.lamports(),
+        )?;
 
-        campaign.closed = true;
+        **admin_info.try_borrow_mut_lamports()? = admin_balance;
+        **campaign_info.try_borrow_mut_lamports()? = rent_exempt_lamports;
 
         Ok(())
     }
@@ -88,22 +107,111 @@ pub mod crowdfunding {
     pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
         
-        if Clock::get().unwrap().unix_timestamp >= campaign.deadline {
-            return Err(CampaignError::DeadlineReached.into());
-        }
-
-        if campaign.closed {
-            return Err(CampaignError::CampaignNotActive.into());
-        }
+        require!(
+            Clock::get()?.unix_timestamp < campaign.deadline,
+            CampaignError::DeadlineReached
+        );
+        require!(!campaign.closed, CampaignError::CampaignNotActive);
 
         campaign.closed = true;
+        campaign.cancelled = true;
+
+        Ok(())
+    }
+
+    /// Returns a donor's recorded donations after the campaign is cancelled
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donation = &mut ctx.accounts.donation;
+        let donator = &ctx.accounts.donator;
+
+        let amount = refundable_amount(campaign, donation.amount)?;
+
+        // Zero the record before moving lamports
+        donation.amount = 0;
+        campaign.amount_raised = campaign.amount_raised
+            .checked_sub(amount)
+            .ok_or(CampaignError::AmountOverflow)?;
+
+        let campaign_info = campaign.to_account_info();
+        let donator_info = donator.to_account_info();
+
+        **campaign_info.try_borrow_mut_lamports()? = campaign_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        **donator_info.try_borrow_mut_lamports()? = donator_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(CampaignError::AmountOverflow)?;
+
+        emit!(Refunded {
+            campaign: campaign.key(),
+            donator: donator.key(),
+            amount,
+        });
 
         Ok(())
     }
 }
 
+/// Returns the campaign total after a donation, enforcing the goal cap
+fn next_amount_raised(
+    amount_raised: u64,
+    amount: u64,
+    target_amount: u64,
+    cap_at_goal: bool,
+) -> Result<u64> {
+    let new_amount_raised = amount_raised
+        .checked_add(amount)
+        .ok_or(CampaignError::AmountOverflow)?;
+
+    require!(
+        !cap_at_goal || new_amount_raised <= target_amount,
+        CampaignError::TargetExceeded
+    );
+
+    Ok(new_amount_raised)
+}
+
+/// Closes the campaign for withdrawal once the deadline has passed.
+/// An instruction runs atomically, so the closed flag alone stops a
+/// second withdrawal and no separate in-progress flag is needed
+fn close_for_withdraw(campaign: &mut Campaign, now: i64) -> Result<()> {
+    require!(now >= campaign.deadline, CampaignError::DeadlineNotReached);
+    require!(!campaign.closed, CampaignError::CampaignClosed);
+    campaign.closed = true;
+    Ok(())
+}
+
+/// Returns the admin balance after withdrawing everything above the
+/// campaign's rent-exempt minimum
+fn withdraw_payout(
+    campaign_balance: u64,
+    rent_exempt_lamports: u64,
+    admin_balance: u64,
+) -> Result<u64> {
+    let withdraw_amount = campaign_balance
+        .checked_sub(rent_exempt_lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+
+    let admin_balance = admin_balance
+        .checked_add(withdraw_amount)
+        .ok_or(CampaignError::AmountOverflow)?;
+
+    Ok(admin_balance)
+}
+
+/// Returns a donor's refundable amount. Only a campaign closed by
+/// cancellation gives refunds
+fn refundable_amount(campaign: &Campaign, donated: u64) -> Result<u64> {
+    require!(campaign.closed && campaign.cancelled, CampaignError::CampaignNotCancelled);
+    require!(donated > 0, CampaignError::NothingToRefund);
+    Ok(donated)
+}
+
 #[derive(Accounts)]
-#[instruction(name: String, description: String, target_amount: u64, deadline: i64)]
+#[instruction(name: String, description: String, target_amount: u64, deadline: i64, cap_at_goal: bool)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -111,7 +219,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32 + 4 + name.len() + 4 + description.len() + 8 + 8 + 8 + 1,
+        space = 8 + 32 + 4 + name.len() + 4 + description.len() + 8 + 8 + 8 + 1 + 1 + 1,
         seeds = [b"campaign", admin.key().as_ref(), name.as_bytes()],
         bump
     )]
@@ -128,6 +236,15 @@ pub struct Donate<'info> {
     #[account(mut)]
     pub campaign: Account<'info, Campaign>,
     
+    #[account(
+        init_if_needed,
+        payer = donator,
+        space = 8 + 32 + 8 + 1,
+        seeds = [b"donation", campaign.key().as_ref(), donator.key().as_ref()],
+        bump
+    )]
+    pub donation: Account<'info, Donation>,
+    
     pub system_program: Program<'info, System>,
 }
 
@@ -140,7 +257,7 @@ pub struct Withdraw<'info> {
         mut,
         seeds = [b"campaign", campaign.admin.as_ref(), campaign.name.as_bytes()],
         bump,
-
+        has_one = admin @ CampaignError::Unauthorized,
     )]
     pub campaign: Account<'info, Campaign>,
     
@@ -156,12 +273,33 @@ pub struct Cancel<'info> {
         mut,
         seeds = [b"campaign", campaign.admin.as_ref(), campaign.name.as_bytes()],
         bump,
+        has_one = admin @ CampaignError::Unauthorized,
     )]
     pub campaign: Account<'info, Campaign>,
     
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(mut)]
+    pub donator: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.admin.as_ref(), campaign.name.as_bytes()],
+        bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    
+    #[account(
+        mut,
+        seeds = [b"donation", campaign.key().as_ref(), donator.key().as_ref()],
+        bump = donation.bump,
+    )]
+    pub donation: Account<'info, Donation>,
+}
+
 #[account]
 pub struct Campaign {
     pub admin: Pubkey,
@@ -171,6 +309,15 @@ pub struct Campaign {
     pub amount_raised: u64,
     pub deadline: i64,
     pub closed: bool,
+    pub cap_at_goal: bool,
+    pub cancelled: bool,
+}
+
+#[account]
+pub struct Donation {
+    pub donator: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
 }
 
 // Error handling
@@ -185,4 +332,137 @@ pub enum CampaignError {
     #[msg("Deadline not reached")]
     DeadlineNotReached,
     
+    #[msg("Deadline has been reached")]
+    DeadlineReached,
+    
+    #[msg("Campaign has ended")]
+    CampaignEnded,
+    
+    #[msg("Campaign is not active")]
+    CampaignNotActive,
+    
+    #[msg("Amount overflow")]
+    AmountOverflow,
+    
+    #[msg("Donation would exceed the campaign target")]
+    TargetExceeded,
+    
+    #[msg("Campaign has not been cancelled")]
+    CampaignNotCancelled,
+    
+    #[msg("Nothing to refund")]
+    NothingToRefund,
+    
+    #[msg("Only the campaign admin can perform this action")]
+    Unauthorized,
+    
+}
+
+// Events
+#[event]
+pub struct Refunded {
+    pub campaign: Pubkey,
+    pub donator: Pubkey,
+    pub amount: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(err: Error) -> ProgramError {
+        err.into()
+    }
+
+    #[test]
+    fn donation_overflowing_u64_is_rejected() {
+        let err = next_amount_raised(u64::MAX - 1, 2, u64::MAX, false).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::AmountOverflow.into()));
+    }
+
+    #[test]
+    fn donation_up_to_u64_max_is_accepted() {
+        assert_eq!(next_amount_raised(u64::MAX - 1, 1, 0, false).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn donation_past_a_capped_goal_is_rejected() {
+        let err = next_amount_raised(90, 11, 100, true).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::TargetExceeded.into()));
+        assert_eq!(next_amount_raised(90, 10, 100, true).unwrap(), 100);
+    }
+    // An open campaign with a 1_000 lamport target and a deadline at t = 100
+    fn campaign() -> Campaign {
+        Campaign {
+            admin: Pubkey::default(),
+            name: String::new(),
+            description: String::new(),
+            target_amount: 1_000,
+            amount_raised: 0,
+            deadline: 100,
+            closed: false,
+            cap_at_goal: false,
+            cancelled: false,
+        }
+    }
+
+    #[test]
+    fn second_withdraw_is_rejected() {
+        let mut campaign = campaign();
+
+        let err = close_for_withdraw(&mut campaign, 99).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::DeadlineNotReached.into()));
+
+        close_for_withdraw(&mut campaign, 100).unwrap();
+        assert!(campaign.closed);
+
+        let err = close_for_withdraw(&mut campaign, 100).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::CampaignClosed.into()));
+    }
+    #[test]
+    fn withdraw_pays_the_admin_the_donated_amount() {
+        let rent = 2_000_000;
+        let donated = next_amount_raised(0, 700, 1_000, false).unwrap();
+        let admin_before = 5_000;
+
+        let admin_after = withdraw_payout(rent + donated, rent, admin_before).unwrap();
+        assert_eq!(admin_after - admin_before, donated);
+    }
+
+    #[test]
+    fn withdraw_below_rent_or_overflowing_the_admin_is_rejected() {
+        let err = withdraw_payout(999, 1_000, 0).unwrap_err();
+        assert_eq!(error_code(err), ProgramError::InsufficientFunds);
+
+        let err = withdraw_payout(1_001, 1_000, u64::MAX).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::AmountOverflow.into()));
+    }
+    #[test]
+    fn cancelled_campaign_refunds_the_donation() {
+        let mut campaign = campaign();
+        campaign.amount_raised = next_amount_raised(0, 300, 1_000, false).unwrap();
+        campaign.closed = true;
+        campaign.cancelled = true;
+
+        let refunded = refundable_amount(&campaign, 300).unwrap();
+        assert_eq!(refunded, 300);
+        assert_eq!(campaign.amount_raised - refunded, 0);
+
+        // The zeroed record has nothing left to refund
+        let err = refundable_amount(&campaign, 0).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::NothingToRefund.into()));
+    }
+
+    #[test]
+    fn only_a_cancelled_campaign_refunds() {
+        // Still open
+        let mut campaign = campaign();
+        let err = refundable_amount(&campaign, 300).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::CampaignNotCancelled.into()));
+
+        // Closed by a withdrawal rather than a cancel
+        close_for_withdraw(&mut campaign, 100).unwrap();
+        let err = refundable_amount(&campaign, 300).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::CampaignNotCancelled.into()));
+    }
 }
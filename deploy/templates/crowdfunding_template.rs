@@ -7,6 +7,24 @@ declare_id!("8a76RhBfP78tuN2WtZaP11ESgeCStcfb9E78Pf9wz4Yg");
 pub mod crowdfunding {
     use super::*;
 
+    pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.paused = false;
+        config.bump = ctx.bumps.config;
+        
+        msg!("Program config initialised");
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+        
+        msg!("Program paused: {}", paused);
+        Ok(())
+    }
+
     pub fn create_campaign(
         ctx: Context<CreateCampaign>,
         name: String,
@@ -15,65 +33,150 @@ pub mod crowdfunding {
         end_time: i64,
         seed: u64,
         bump: u8,
+        min_contribution: u64,
+        max_per_contributor: u64,
+        category: u8,
+        image_uri: String,
     ) -> Result<()> {
-        require!(target_amount > 0, CampaignError::InvalidAmount);
+        validate_limits(target_amount, min_contribution, max_per_contributor)?;
         require!(
             end_time > Clock::get()?.unix_timestamp,
             CampaignError::InvalidEndTime
         );
         require!(name.len() <= 50, CampaignError::NameTooLong);
         require!(description.len() <= 255, CampaignError::DescriptionTooLong);
+        require!(
+            CampaignCategory::from_u8(category).is_some(),
+            CampaignError::InvalidCategory
+        );
+        require!(image_uri.len() <= 200, CampaignError::ImageUriTooLong);
 
         let campaign = &mut ctx.accounts.campaign;
         campaign.creator = ctx.accounts.creator.key();
         campaign.name = name;
         campaign.description = description;
+        campaign.category = category;
+        campaign.image_uri = image_uri;
         campaign.target_amount = target_amount;
         campaign.raised_amount = 0;
+        campaign.min_contribution = min_contribution;
+        campaign.max_per_contributor = max_per_contributor;
+        campaign.matcher = Pubkey::default();
+        campaign.match_ratio_bps = 0;
+        campaign.match_pool_remaining = 0;
+        campaign.matched_total = 0;
         campaign.end_time = end_time;
         campaign.is_active = true;
         campaign.is_successful = false;
+        campaign.extensions_used = 0;
+        campaign.contributor_count = 0;
         campaign.seed = seed;
         campaign.bump = bump;
         campaign.created_at = Clock::get()?.unix_timestamp;
         campaign.last_updated_at = campaign.created_at;
         
+        emit!(CampaignCreated {
+            campaign: campaign.key(),
+            creator: campaign.creator,
+            category: campaign.category,
+            image_uri: campaign.image_uri.clone(),
+            target_amount,
+            end_time,
+        });
+        
         msg!("Campaign created successfully");
         Ok(())
     }
 
     pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
+        let contribution = &mut ctx.accounts.contribution;
         let contributor = &ctx.accounts.contributor;
         let current_time = Clock::get()?.unix_timestamp;
         
-        // Check if campaign is still active
-        require!(campaign.is_active, CampaignError::CampaignNotActive);
+        // Check the program is not paused and the campaign accepts this amount
+        validate_contribution(&ctx.accounts.config, campaign, amount, current_time)?;
+        
+        // Update the campaign totals and the contributor's record
+        let matched_amount = apply_contribution(campaign, contribution, amount)?;
+        campaign.last_updated_at = current_time;
+        
+        contribution.campaign = campaign.key();
+        contribution.contributor = contributor.key();
+        contribution.bump = ctx.bumps.contribution;
+        
+        // Transfer lamports from contributor to campaign account
+        let transfer_ix = system_program::Transfer {
+            from: contributor.to_account_info(),
+            to: campaign.to_account_info(),
+        };
+        
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            transfer_ix,
+        );
+        
+        system_program::transfer(cpi_ctx, amount)?;
+        
+        if matched_amount > 0 {
+            emit!(ContributionMatched {
+                campaign: campaign.key(),
+                contributor: contributor.key(),
+                amount,
+                matched_amount,
+                match_pool_remaining: campaign.match_pool_remaining,
+            });
+        }
+        
+        emit!(ContributionMade {
+            campaign: campaign.key(),
+            contributor: contributor.key(),
+            amount,
+            contributor_count: campaign.contributor_count,
+        });
         
-        // Check if campaign hasn't ended
+        msg!("Contributed {} lamports to campaign", amount);
+        Ok(())
+    }
+
+    pub fn set_matcher(
+        ctx: Context<SetMatcher>,
+        matcher: Pubkey,
+        match_ratio_bps: u16,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        
+        require!(match_ratio_bps <= 10_000, CampaignError::InvalidMatchRatio);
+        
+        // Match terms are fixed once the matcher has put funds in
         require!(
-            current_time <= campaign.end_time,
-            CampaignError::CampaignEnded
+            campaign.match_pool_remaining == 0 && campaign.matched_total == 0,
+            CampaignError::MatchPoolFunded
         );
         
-        // Check contribution amount
+        campaign.matcher = matcher;
+        campaign.match_ratio_bps = match_ratio_bps;
+        campaign.last_updated_at = Clock::get()?.unix_timestamp;
+        
+        msg!("Campaign matcher set to {}", matcher);
+        Ok(())
+    }
+
+    pub fn fund_match_pool(ctx: Context<FundMatchPool>, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let matcher = &ctx.accounts.matcher;
+        
+        require!(campaign.is_active, CampaignError::CampaignNotActive);
         require!(amount > 0, CampaignError::InvalidAmount);
         
-        // Update campaign state
-        campaign.raised_amount = campaign.raised_amount
+        campaign.match_pool_remaining = campaign.match_pool_remaining
             .checked_add(amount)
             .ok_or(CampaignError::AmountOverflow)?;
+        campaign.last_updated_at = Clock::get()?.unix_timestamp;
         
-        campaign.last_updated_at = current_time;
-        
-        // Check if target has been reached
-        if campaign.raised_amount >= campaign.target_amount {
-            campaign.is_successful = true;
-        }
-        
-        // Transfer lamports from contributor to campaign account
+        // Transfer lamports from matcher to campaign account
         let transfer_ix = system_program::Transfer {
-            from: contributor.to_account_info(),
+            from: matcher.to_account_info(),
             to: campaign.to_account_info(),
         };
         
@@ -84,7 +187,80 @@ pub mod crowdfunding {
         
         system_program::transfer(cpi_ctx, amount)?;
         
-        msg!("Contributed {} lamports to campaign", amount);
+        msg!("Match pool funded with {} lamports", amount);
+        Ok(())
+    }
+
+    pub fn reclaim_match(ctx: Context<ReclaimMatch>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let matcher = &ctx.accounts.matcher;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // Check the program has not been paused
+        require_not_paused(&ctx.accounts.config)?;
+        
+        // Return the unused pool, plus matched funds if the campaign failed
+        let amount = take_match_refund(campaign)?;
+        campaign.last_updated_at = current_time;
+        
+        // Transfer lamports from campaign to matcher
+        let campaign_info = campaign.to_account_info();
+        let matcher_info = matcher.to_account_info();
+        
+        **campaign_info.try_borrow_mut_lamports()? = campaign_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(CampaignError::InsufficientFunds)?;
+        
+        **matcher_info.try_borrow_mut_lamports()? = matcher_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(CampaignError::AmountOverflow)?;
+        
+        emit!(MatchReclaimed {
+            campaign: campaign.key(),
+            matcher: matcher.key(),
+            amount,
+        });
+        
+        msg!("Returned {} lamports to matcher", amount);
+        Ok(())
+    }
+
+    pub fn extend_deadline(ctx: Context<ExtendDeadline>, new_end_time: i64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // Only an active campaign that has not ended can be extended
+        require!(campaign.is_active, CampaignError::CampaignNotActive);
+        require!(
+            current_time <= campaign.end_time,
+            CampaignError::CampaignEnded
+        );
+        
+        // New deadline must be later than the current one
+        require!(
+            new_end_time > campaign.end_time,
+            CampaignError::InvalidExtension
+        );
+        require!(
+            campaign.extensions_used < CampaignAccount::MAX_EXTENSIONS,
+            CampaignError::TooManyExtensions
+        );
+        
+        let old_end_time = campaign.end_time;
+        campaign.end_time = new_end_time;
+        campaign.extensions_used += 1;
+        campaign.last_updated_at = current_time;
+        
+        emit!(DeadlineExtended {
+            campaign: campaign.key(),
+            old_end_time,
+            new_end_time,
+            extensions_used: campaign.extensions_used,
+        });
+        
+        msg!("Campaign deadline extended to {}", new_end_time);
         Ok(())
     }
 
@@ -93,6 +269,9 @@ pub mod crowdfunding {
         let creator = &ctx.accounts.creator;
         let current_time = Clock::get()?.unix_timestamp;
         
+        // Check the program has not been paused
+        require_not_paused(&ctx.accounts.config)?;
+        
         // Check if creator is authorized
         require!(
             creator.key() == campaign.creator,
@@ -140,31 +319,98 @@ pub mod crowdfunding {
         Ok(())
     }
 
+    pub fn withdraw_on_success(ctx: Context<WithdrawFunds>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // Check the program has not been paused
+        require_not_paused(&ctx.accounts.config)?;
+        
+        // Funded campaigns can be withdrawn without waiting for end_time
+        let amount = take_success_payout(campaign)?;
+        campaign.last_updated_at = current_time;
+        
+        // Transfer lamports from campaign to creator
+        let campaign_info = campaign.to_account_info();
+        let creator_info = creator.to_account_info();
+        
+        **campaign_info.try_borrow_mut_lamports()? = campaign_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(CampaignError::InsufficientFunds)?;
+        
+        **creator_info.try_borrow_mut_lamports()? = creator_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(CampaignError::AmountOverflow)?;
+        
+        emit!(SuccessfulWithdrawal {
+            campaign: campaign.key(),
+            creator: creator.key(),
+            amount,
+            before_end_time: current_time <= campaign.end_time,
+        });
+        
+        msg!("Funded campaign: withdrew {} lamports", amount);
+        Ok(())
+    }
+
     pub fn refund(ctx: Context<Refund>, amount: u64) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
+        let contribution = &mut ctx.accounts.contribution;
         let contributor = &ctx.accounts.contributor;
         let current_time = Clock::get()?.unix_timestamp;
         
-        // Check if campaign has ended and was not successful
-        require!(!campaign.is_active, CampaignError::CampaignStillActive);
-        require!(!campaign.is_successful, CampaignError::CampaignSuccessful);
-        require!(
-            current_time > campaign.end_time,
-            CampaignError::CampaignNotEnded
-        );
+        // Check the program is not paused and the campaign ended unsuccessfully
+        validate_refund(&ctx.accounts.config, campaign, current_time)?;
         
-        // Check refund amount
-        require!(amount > 0, CampaignError::InvalidAmount);
-        require!(
-            amount <= campaign.raised_amount,
-            CampaignError::InsufficientFunds
-        );
+        // Check the amount against the recorded balance and update both accounts
+        let fully_refunded = apply_refund(campaign, contribution, amount)?;
+        campaign.last_updated_at = current_time;
         
-        // Update campaign state
-        campaign.raised_amount = campaign.raised_amount
+        // Transfer lamports from campaign to contributor
+        let campaign_info = campaign.to_account_info();
+        let contributor_info = contributor.to_account_info();
+        
+        **campaign_info.try_borrow_mut_lamports()? = campaign_info
+            .lamports()
             .checked_sub(amount)
+            .ok_or(CampaignError::InsufficientFunds)?;
+        
+        **contributor_info.try_borrow_mut_lamports()? = contributor_info
+            .lamports()
+            .checked_add(amount)
             .ok_or(CampaignError::AmountOverflow)?;
         
+        emit!(ContributionRefunded {
+            campaign: campaign.key(),
+            contributor: contributor.key(),
+            amount,
+            contributor_count: campaign.contributor_count,
+        });
+        
+        // Close the contribution record once fully refunded
+        if fully_refunded {
+            ctx.accounts.contribution.close(ctx.accounts.contributor.to_account_info())?;
+        }
+        
+        msg!("Refunded {} lamports to contributor", amount);
+        Ok(())
+    }
+    
+    pub fn refund_all(ctx: Context<Refund>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let contribution = &mut ctx.accounts.contribution;
+        let contributor = &ctx.accounts.contributor;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // Check the program is not paused and the campaign ended unsuccessfully
+        validate_refund(&ctx.accounts.config, campaign, current_time)?;
+        
+        // Refund the contributor's full recorded balance
+        let amount = contribution.amount;
+        apply_refund(campaign, contribution, amount)?;
         campaign.last_updated_at = current_time;
         
         // Transfer lamports from campaign to contributor
@@ -181,7 +427,58 @@ pub mod crowdfunding {
             .checked_add(amount)
             .ok_or(CampaignError::AmountOverflow)?;
         
-        msg!("Refunded {} lamports to contributor", amount);
+        emit!(ContributionRefunded {
+            campaign: campaign.key(),
+            contributor: contributor.key(),
+            amount,
+            contributor_count: campaign.contributor_count,
+        });
+        
+        // The record is now empty, so close it
+        ctx.accounts.contribution.close(ctx.accounts.contributor.to_account_info())?;
+        
+        msg!("Refunded all {} lamports to contributor", amount);
+        Ok(())
+    }
+    
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // Only an inactive campaign past its deadline can be swept
+        require!(!campaign.is_active, CampaignError::CampaignStillActive);
+        require!(
+            current_time > campaign.end_time,
+            CampaignError::CampaignNotEnded
+        );
+        
+        // Everything above rent and the funds still owed to contributors
+        // or the matcher is residual
+        let campaign_info = campaign.to_account_info();
+        let creator_info = creator.to_account_info();
+        let rent_exempt_lamports = Rent::get()?.minimum_balance(campaign_info.data_len());
+        let available = campaign_info.lamports().saturating_sub(rent_exempt_lamports);
+        let amount = take_dust(campaign, available)?;
+        campaign.last_updated_at = current_time;
+        
+        **campaign_info.try_borrow_mut_lamports()? = campaign_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(CampaignError::InsufficientFunds)?;
+        
+        **creator_info.try_borrow_mut_lamports()? = creator_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(CampaignError::AmountOverflow)?;
+        
+        emit!(DustSwept {
+            campaign: campaign.key(),
+            creator: creator.key(),
+            amount,
+        });
+        
+        msg!("Swept {} residual lamports to creator", amount);
         Ok(())
     }
     
@@ -194,14 +491,8 @@ pub mod crowdfunding {
             CampaignError::Unauthorized
         );
         
-        // Check if campaign is inactive
-        require!(!campaign.is_active, CampaignError::CampaignStillActive);
-        
-        // Check if all funds have been withdrawn or refunded
-        require!(
-            campaign.raised_amount == 0,
-            CampaignError::FundsRemaining
-        );
+        // Check the campaign is inactive and all funds are paid out
+        validate_close(campaign)?;
         
         // Transfer rent exemption back to creator
         // (close = creator attribute handles this automatically)
@@ -211,6 +502,269 @@ pub mod crowdfunding {
     }
 }
 
+// Checks the target and the per-contribution limits set at creation
+fn validate_limits(target_amount: u64, min_contribution: u64, max_per_contributor: u64) -> Result<()> {
+    require!(target_amount > 0, CampaignError::InvalidAmount);
+    require!(
+        min_contribution <= target_amount,
+        CampaignError::InvalidMinimum
+    );
+    require!(
+        max_per_contributor <= target_amount,
+        CampaignError::InvalidContributorCap
+    );
+    Ok(())
+}
+
+// Returns a contributor's running total after a contribution, enforcing
+// the per-contributor cap (a zero cap means unlimited)
+fn next_contribution_total(recorded: u64, amount: u64, max_per_contributor: u64) -> Result<u64> {
+    let total = recorded
+        .checked_add(amount)
+        .ok_or(CampaignError::AmountOverflow)?;
+    require!(
+        max_per_contributor == 0 || total <= max_per_contributor,
+        CampaignError::ContributorCapExceeded
+    );
+    Ok(total)
+}
+
+// Returns the match for a contribution at the given ratio, clamped to
+// what is left in the pool
+fn compute_match(amount: u64, match_ratio_bps: u16, match_pool_remaining: u64) -> Result<u64> {
+    let matched = amount as u128 * match_ratio_bps as u128 / 10_000;
+    let matched = u64::try_from(matched).map_err(|_| CampaignError::AmountOverflow)?;
+    Ok(matched.min(match_pool_remaining))
+}
+
+// Removes what the matcher can reclaim from the campaign and returns it.
+// The unused pool always goes back; matched funds only if the campaign
+// failed, since a successful one paid them to the creator
+fn take_match_refund(campaign: &mut CampaignAccount) -> Result<u64> {
+    let returned_matches = if campaign.is_successful {
+        0
+    } else {
+        campaign.matched_total
+    };
+    let amount = campaign.match_pool_remaining
+        .checked_add(returned_matches)
+        .ok_or(CampaignError::AmountOverflow)?;
+    require!(amount > 0, CampaignError::NothingToRefund);
+    
+    campaign.raised_amount = campaign.raised_amount
+        .checked_sub(returned_matches)
+        .ok_or(CampaignError::AmountOverflow)?;
+    campaign.matched_total -= returned_matches;
+    campaign.match_pool_remaining = 0;
+    Ok(amount)
+}
+
+// Applies a contribution to the campaign and the contributor's record,
+// drawing any match from the pool, and returns the matched amount
+fn apply_contribution(
+    campaign: &mut CampaignAccount,
+    contribution: &mut ContributionAccount,
+    amount: u64,
+) -> Result<u64> {
+    // Match the contribution from the pool, clamped to what remains
+    let matched_amount = compute_match(
+        amount,
+        campaign.match_ratio_bps,
+        campaign.match_pool_remaining,
+    )?;
+    campaign.match_pool_remaining -= matched_amount;
+    campaign.matched_total = campaign.matched_total
+        .checked_add(matched_amount)
+        .ok_or(CampaignError::AmountOverflow)?;
+    
+    campaign.raised_amount = campaign.raised_amount
+        .checked_add(amount)
+        .and_then(|raised| raised.checked_add(matched_amount))
+        .ok_or(CampaignError::AmountOverflow)?;
+    
+    // A freshly created record means a new backer
+    if contribution.amount == 0 {
+        campaign.contributor_count = campaign.contributor_count
+            .checked_add(1)
+            .ok_or(CampaignError::AmountOverflow)?;
+    }
+    
+    // Record the contributor's cumulative amount for refunds
+    contribution.amount = next_contribution_total(
+        contribution.amount,
+        amount,
+        campaign.max_per_contributor,
+    )?;
+    
+    // Check if target has been reached
+    if campaign.raised_amount >= campaign.target_amount {
+        campaign.is_successful = true;
+    }
+    
+    Ok(matched_amount)
+}
+
+// Closes a funded campaign and returns everything raised for the creator.
+// There is deliberately no end_time check
+fn take_success_payout(campaign: &mut CampaignAccount) -> Result<u64> {
+    require!(campaign.is_active, CampaignError::CampaignNotActive);
+    require!(campaign.is_successful, CampaignError::CampaignNotSuccessful);
+    
+    let amount = campaign.raised_amount;
+    campaign.raised_amount = 0;
+    campaign.is_active = false;
+    Ok(amount)
+}
+
+// Returns how much of `available` (the balance above rent) sweep_dust can
+// send to the creator. Raised funds only count as the creator's once
+// nobody can claim them back: after success, or once every contributor
+// is refunded. Matched amounts on a failed campaign and the unused pool
+// stay for reclaim_match, so raised_amount is only cleared of the
+// creator's share rather than zeroed
+fn take_dust(campaign: &mut CampaignAccount, available: u64) -> Result<u64> {
+    let creator_share = if campaign.is_successful {
+        campaign.raised_amount
+    } else if campaign.contributor_count == 0 {
+        campaign.raised_amount.saturating_sub(campaign.matched_total)
+    } else {
+        0
+    };
+    
+    let owed = (campaign.raised_amount - creator_share)
+        .checked_add(campaign.match_pool_remaining)
+        .ok_or(CampaignError::AmountOverflow)?;
+    
+    campaign.raised_amount -= creator_share;
+    Ok(available.saturating_sub(owed))
+}
+
+// A campaign can be closed once it is inactive and all funds are paid out
+fn validate_close(campaign: &CampaignAccount) -> Result<()> {
+    require!(!campaign.is_active, CampaignError::CampaignStillActive);
+    require!(
+        campaign.raised_amount == 0,
+        CampaignError::FundsRemaining
+    );
+    Ok(())
+}
+
+// Takes a refund out of the contributor's record and the campaign total.
+// Returns whether the record is now empty, in which case the contributor
+// no longer counts as a backer
+fn apply_refund(
+    campaign: &mut CampaignAccount,
+    contribution: &mut ContributionAccount,
+    amount: u64,
+) -> Result<bool> {
+    require!(contribution.amount > 0, CampaignError::NothingToRefund);
+    require!(amount > 0, CampaignError::InvalidAmount);
+    require!(
+        amount <= contribution.amount,
+        CampaignError::InsufficientFunds
+    );
+    require!(
+        amount <= campaign.raised_amount,
+        CampaignError::InsufficientFunds
+    );
+    
+    contribution.amount = contribution.amount
+        .checked_sub(amount)
+        .ok_or(CampaignError::AmountOverflow)?;
+    campaign.raised_amount = campaign.raised_amount
+        .checked_sub(amount)
+        .ok_or(CampaignError::AmountOverflow)?;
+    
+    let fully_refunded = contribution.amount == 0;
+    if fully_refunded {
+        campaign.contributor_count = campaign.contributor_count
+            .checked_sub(1)
+            .ok_or(CampaignError::AmountOverflow)?;
+    }
+    
+    Ok(fully_refunded)
+}
+
+// Kill switch check shared by every instruction that moves campaign funds
+fn require_not_paused(config: &Config) -> Result<()> {
+    require!(!config.paused, CampaignError::ProgramPaused);
+    Ok(())
+}
+
+// Checks for contribute: the campaign must be open and the amount must
+// meet its minimum (a zero minimum means no minimum)
+fn validate_contribution(
+    config: &Config,
+    campaign: &CampaignAccount,
+    amount: u64,
+    current_time: i64,
+) -> Result<()> {
+    require_not_paused(config)?;
+    require!(campaign.is_active, CampaignError::CampaignNotActive);
+    require!(
+        current_time <= campaign.end_time,
+        CampaignError::CampaignEnded
+    );
+    require!(amount > 0, CampaignError::InvalidAmount);
+    require!(
+        amount >= campaign.min_contribution,
+        CampaignError::BelowMinimum
+    );
+    Ok(())
+}
+
+// Shared checks for refund and refund_all: the campaign must have ended
+// without reaching its target
+fn validate_refund(config: &Config, campaign: &CampaignAccount, current_time: i64) -> Result<()> {
+    require_not_paused(config)?;
+    require!(!campaign.is_active, CampaignError::CampaignStillActive);
+    require!(!campaign.is_successful, CampaignError::CampaignSuccessful);
+    require!(
+        current_time > campaign.end_time,
+        CampaignError::CampaignNotEnded
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Crowdfunding>,
+    
+    // Only the upgrade authority can claim the pause admin role
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CampaignError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CampaignError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+}
+
 #[derive(Accounts)]
 #[instruction(name: String, description: String, target_amount: u64, end_time: i64, seed: u64, bump: u8)]
 pub struct CreateCampaign<'info> {
@@ -242,9 +796,92 @@ pub struct Contribute<'info> {
     )]
     pub campaign: Account<'info, CampaignAccount>,
     
+    #[account(
+        init_if_needed,
+        payer = contributor,
+        space = 8 + ContributionAccount::SIZE,
+        seeds = [b"contribution", campaign.key().as_ref(), contributor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, ContributionAccount>,
+    
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMatcher<'info> {
+    #[account(
+        constraint = creator.key() == campaign.creator @ CampaignError::Unauthorized
+    )]
+    pub creator: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.seed.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+        constraint = campaign.is_active @ CampaignError::CampaignNotActive
+    )]
+    pub campaign: Account<'info, CampaignAccount>,
+}
+
+#[derive(Accounts)]
+pub struct FundMatchPool<'info> {
+    #[account(
+        mut,
+        constraint = matcher.key() == campaign.matcher @ CampaignError::Unauthorized
+    )]
+    pub matcher: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.seed.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+        constraint = campaign.is_active @ CampaignError::CampaignNotActive
+    )]
+    pub campaign: Account<'info, CampaignAccount>,
+    
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ReclaimMatch<'info> {
+    #[account(
+        mut,
+        constraint = matcher.key() == campaign.matcher @ CampaignError::Unauthorized
+    )]
+    pub matcher: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.seed.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+        constraint = !campaign.is_active @ CampaignError::CampaignStillActive
+    )]
+    pub campaign: Account<'info, CampaignAccount>,
+    
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendDeadline<'info> {
+    #[account(
+        constraint = creator.key() == campaign.creator @ CampaignError::Unauthorized
+    )]
+    pub creator: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.seed.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+        constraint = campaign.is_active @ CampaignError::CampaignNotActive
+    )]
+    pub campaign: Account<'info, CampaignAccount>,
+}
+
 #[derive(Accounts)]
 pub struct WithdrawFunds<'info> {
     #[account(
@@ -260,6 +897,9 @@ pub struct WithdrawFunds<'info> {
     )]
     pub campaign: Account<'info, CampaignAccount>,
     
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
     pub system_program: Program<'info, System>,
 }
 
@@ -277,9 +917,36 @@ pub struct Refund<'info> {
     )]
     pub campaign: Account<'info, CampaignAccount>,
     
+    #[account(
+        mut,
+        seeds = [b"contribution", campaign.key().as_ref(), contributor.key().as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Account<'info, ContributionAccount>,
+    
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    #[account(
+        mut,
+        constraint = creator.key() == campaign.creator @ CampaignError::Unauthorized
+    )]
+    pub creator: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.seed.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+        constraint = !campaign.is_active @ CampaignError::CampaignStillActive
+    )]
+    pub campaign: Account<'info, CampaignAccount>,
+}
+
 #[derive(Accounts)]
 pub struct CloseCampaign<'info> {
     #[account(
@@ -301,16 +968,41 @@ pub struct CloseCampaign<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl Config {
+    // Size calculation in bytes:
+    // - Admin pubkey: 32 bytes
+    // - Paused flag: 1 byte (bool)
+    // - Bump: 1 byte (u8)
+    pub const SIZE: usize = 32 + 1 + 1;
+}
+
 #[account]
 pub struct CampaignAccount {
     pub creator: Pubkey,
     pub name: String,
     pub description: String,
+    pub category: u8,
+    pub image_uri: String,
     pub target_amount: u64,
     pub raised_amount: u64,
+    pub min_contribution: u64,
+    pub max_per_contributor: u64,
+    pub matcher: Pubkey,
+    pub match_ratio_bps: u16,
+    pub match_pool_remaining: u64,
+    pub matched_total: u64,
     pub end_time: i64,
     pub is_active: bool,
     pub is_successful: bool,
+    pub extensions_used: u8,
+    pub contributor_count: u64,
     pub seed: u64,
     pub bump: u8,
     pub created_at: i64,
@@ -322,16 +1014,65 @@ impl CampaignAccount {
     // - Pubkey: 32 bytes
     // - Name string: 4 bytes (length) + 50 bytes (max content)
     // - Description string: 4 bytes (length) + 255 bytes (max content)
+    // - Category: 1 byte (u8)
+    // - Image URI string: 4 bytes (length) + 200 bytes (max content)
     // - Target amount: 8 bytes (u64)
     // - Raised amount: 8 bytes (u64)
+    // - Minimum contribution: 8 bytes (u64)
+    // - Max per contributor: 8 bytes (u64)
+    // - Matcher pubkey: 32 bytes
+    // - Match ratio: 2 bytes (u16, basis points)
+    // - Match pool remaining: 8 bytes (u64)
+    // - Matched total: 8 bytes (u64)
     // - End time: 8 bytes (i64)
     // - Is active flag: 1 byte (bool)
     // - Is successful flag: 1 byte (bool)
+    // - Extensions used: 1 byte (u8)
+    // - Contributor count: 8 bytes (u64)
     // - Seed: 8 bytes (u64)
     // - Bump: 1 byte (u8)
     // - Created at: 8 bytes (i64)
     // - Last updated at: 8 bytes (i64)
-    pub const SIZE: usize = 32 + 4 + 50 + 4 + 255 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 8 + 8;
+    pub const SIZE: usize = 32 + 4 + 50 + 4 + 255 + 1 + 4 + 200 + 8 + 8 + 8 + 8 + 32 + 2 + 8 + 8 + 8 + 1 + 1 + 1 + 8 + 8 + 1 + 8 + 8;
+    
+    pub const MAX_EXTENSIONS: u8 = 3;
+}
+
+#[account]
+pub struct ContributionAccount {
+    pub campaign: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl ContributionAccount {
+    // Size calculation in bytes:
+    // - Campaign pubkey: 32 bytes
+    // - Contributor pubkey: 32 bytes
+    // - Amount: 8 bytes (u64)
+    // - Bump: 1 byte (u8)
+    pub const SIZE: usize = 32 + 32 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CampaignCategory {
+    Charity,
+    Tech,
+    Art,
+    Community,
+}
+
+impl CampaignCategory {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(CampaignCategory::Charity),
+            1 => Some(CampaignCategory::Tech),
+            2 => Some(CampaignCategory::Art),
+            3 => Some(CampaignCategory::Community),
+            _ => None,
+        }
+    }
 }
 
 #[error_code]
@@ -364,7 +1105,7 @@ pub enum CampaignError {
     CampaignSuccessful,
     
     #[msg("Unauthorised access")]
-    Unauthorised,
+    Unauthorized,
     
     #[msg("Insufficient funds")]
     InsufficientFunds,
@@ -374,4 +1115,380 @@ pub enum CampaignError {
     
     #[msg("All funds must be withdrawn or refunded before closing")]
     FundsRemaining,
-} 
\ No newline at end of file
+    
+    #[msg("No recorded contribution to refund")]
+    NothingToRefund,
+    
+    #[msg("New end time must be later than the current end time")]
+    InvalidExtension,
+    
+    #[msg("Maximum number of deadline extensions reached")]
+    TooManyExtensions,
+    
+    #[msg("Contribution is below the campaign minimum")]
+    BelowMinimum,
+    
+    #[msg("Minimum contribution cannot exceed the target amount")]
+    InvalidMinimum,
+    
+    #[msg("Program is paused")]
+    ProgramPaused,
+    
+    #[msg("Contribution would exceed the per-contributor cap")]
+    ContributorCapExceeded,
+    
+    #[msg("Per-contributor cap cannot exceed the target amount")]
+    InvalidContributorCap,
+    
+    #[msg("Campaign has not reached its target")]
+    CampaignNotSuccessful,
+    
+    #[msg("Match ratio cannot exceed 10000 basis points")]
+    InvalidMatchRatio,
+    
+    #[msg("Invalid campaign category")]
+    InvalidCategory,
+    
+    #[msg("Image URI too long (maximum 200 characters)")]
+    ImageUriTooLong,
+    
+    #[msg("Match terms cannot change once the pool is funded")]
+    MatchPoolFunded,
+}
+
+// Events
+#[event]
+pub struct CampaignCreated {
+    pub campaign: Pubkey,
+    pub creator: Pubkey,
+    pub category: u8,
+    pub image_uri: String,
+    pub target_amount: u64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct DeadlineExtended {
+    pub campaign: Pubkey,
+    pub old_end_time: i64,
+    pub new_end_time: i64,
+    pub extensions_used: u8,
+}
+
+#[event]
+pub struct SuccessfulWithdrawal {
+    pub campaign: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub before_end_time: bool,
+}
+
+#[event]
+pub struct ContributionMatched {
+    pub campaign: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub matched_amount: u64,
+    pub match_pool_remaining: u64,
+}
+
+#[event]
+pub struct DustSwept {
+    pub campaign: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ContributionMade {
+    pub campaign: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub contributor_count: u64,
+}
+
+#[event]
+pub struct ContributionRefunded {
+    pub campaign: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub contributor_count: u64,
+}
+
+#[event]
+pub struct MatchReclaimed {
+    pub campaign: Pubkey,
+    pub matcher: Pubkey,
+    pub amount: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(err: Error) -> ProgramError {
+        err.into()
+    }
+
+    fn config(paused: bool) -> Config {
+        Config {
+            admin: Pubkey::default(),
+            paused,
+            bump: 0,
+        }
+    }
+
+    // An open campaign with a 1_000 lamport target ending at t = 100
+    fn campaign() -> CampaignAccount {
+        CampaignAccount {
+            creator: Pubkey::default(),
+            name: String::new(),
+            description: String::new(),
+            category: 0,
+            image_uri: String::new(),
+            target_amount: 1_000,
+            raised_amount: 0,
+            min_contribution: 0,
+            max_per_contributor: 0,
+            matcher: Pubkey::default(),
+            match_ratio_bps: 0,
+            match_pool_remaining: 0,
+            matched_total: 0,
+            end_time: 100,
+            is_active: true,
+            is_successful: false,
+            extensions_used: 0,
+            contributor_count: 0,
+            seed: 0,
+            bump: 0,
+            created_at: 0,
+            last_updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn contributions_fail_while_paused_and_resume_after_unpausing() {
+        let mut config = config(true);
+        let campaign = campaign();
+
+        let err = validate_contribution(&config, &campaign, 10, 50).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::ProgramPaused.into()));
+
+        config.paused = false;
+        assert!(validate_contribution(&config, &campaign, 10, 50).is_ok());
+    }
+
+    #[test]
+    fn refunds_fail_while_paused() {
+        let mut campaign = campaign();
+        campaign.is_active = false;
+
+        let err = validate_refund(&config(true), &campaign, 101).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::ProgramPaused.into()));
+        assert!(validate_refund(&config(false), &campaign, 101).is_ok());
+    }
+    #[test]
+    fn contributions_split_across_the_cap() {
+        // 60 + 40 lands exactly on a 100 lamport cap
+        let total = next_contribution_total(0, 60, 100).unwrap();
+        assert_eq!(next_contribution_total(total, 40, 100).unwrap(), 100);
+
+        // 60 + 41 goes one lamport over it
+        let err = next_contribution_total(total, 41, 100).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::ContributorCapExceeded.into()));
+    }
+
+    #[test]
+    fn zero_cap_is_unlimited() {
+        assert_eq!(next_contribution_total(u64::MAX - 1, 1, 0).unwrap(), u64::MAX);
+        let err = next_contribution_total(u64::MAX, 1, 0).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::AmountOverflow.into()));
+    }
+
+    #[test]
+    fn cap_above_the_target_is_rejected() {
+        assert!(validate_limits(1_000, 0, 1_000).is_ok());
+        let err = validate_limits(1_000, 0, 1_001).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::InvalidContributorCap.into()));
+    }
+    #[test]
+    fn match_is_drawn_from_the_pool_until_exhausted() {
+        // A 50% match from a 100 lamport pool
+        let mut pool = 100;
+
+        let matched = compute_match(120, 5_000, pool).unwrap();
+        assert_eq!(matched, 60);
+        pool -= matched;
+
+        // Exactly exhausts the pool
+        let matched = compute_match(80, 5_000, pool).unwrap();
+        assert_eq!(matched, 40);
+        pool -= matched;
+        assert_eq!(pool, 0);
+
+        // Nothing left to match
+        assert_eq!(compute_match(80, 5_000, pool).unwrap(), 0);
+    }
+
+    #[test]
+    fn match_is_clamped_to_the_remaining_pool() {
+        assert_eq!(compute_match(1_000, 10_000, 30).unwrap(), 30);
+    }
+
+    #[test]
+    fn match_overflowing_u64_is_rejected() {
+        assert_eq!(compute_match(u64::MAX, 10_000, u64::MAX).unwrap(), u64::MAX);
+        let err = compute_match(u64::MAX, u16::MAX, u64::MAX).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::AmountOverflow.into()));
+    }
+
+    #[test]
+    fn failed_campaign_returns_pool_and_matches_to_matcher() {
+        // Contributors were refunded, leaving only the 60 matched lamports
+        let mut campaign = campaign();
+        campaign.is_active = false;
+        campaign.raised_amount = 60;
+        campaign.matched_total = 60;
+        campaign.match_pool_remaining = 40;
+
+        assert_eq!(take_match_refund(&mut campaign).unwrap(), 100);
+        assert_eq!(campaign.raised_amount, 0);
+        assert_eq!(campaign.matched_total, 0);
+        assert_eq!(campaign.match_pool_remaining, 0);
+
+        let err = take_match_refund(&mut campaign).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::NothingToRefund.into()));
+    }
+
+    #[test]
+    fn successful_campaign_returns_only_the_unused_pool() {
+        let mut campaign = campaign();
+        campaign.is_active = false;
+        campaign.is_successful = true;
+        campaign.matched_total = 60;
+        campaign.match_pool_remaining = 40;
+
+        assert_eq!(take_match_refund(&mut campaign).unwrap(), 40);
+        assert_eq!(campaign.matched_total, 60);
+    }
+    fn contribution() -> ContributionAccount {
+        ContributionAccount {
+            campaign: Pubkey::default(),
+            contributor: Pubkey::default(),
+            amount: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn backers_are_counted_once_and_dropped_on_full_refund() {
+        let mut campaign = campaign();
+        let mut alice = contribution();
+        let mut bob = contribution();
+
+        apply_contribution(&mut campaign, &mut alice, 100).unwrap();
+        apply_contribution(&mut campaign, &mut bob, 50).unwrap();
+        // A repeat contribution is not a new backer
+        apply_contribution(&mut campaign, &mut bob, 50).unwrap();
+        assert_eq!(campaign.contributor_count, 2);
+        assert_eq!(campaign.raised_amount, 200);
+
+        // A partial refund keeps the backer, a full one drops them
+        campaign.is_active = false;
+        assert!(!apply_refund(&mut campaign, &mut bob, 40).unwrap());
+        assert_eq!(campaign.contributor_count, 2);
+        assert!(apply_refund(&mut campaign, &mut bob, 60).unwrap());
+        assert_eq!(campaign.contributor_count, 1);
+        assert_eq!(campaign.raised_amount, 100);
+    }
+
+    #[test]
+    fn refund_with_no_counted_backer_is_reported() {
+        // A record with a balance but no counted backer is an accounting bug
+        let mut campaign = campaign();
+        campaign.raised_amount = 10;
+        let mut record = contribution();
+        record.amount = 10;
+
+        let err = apply_refund(&mut campaign, &mut record, 10).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::AmountOverflow.into()));
+    }
+    #[test]
+    fn funded_campaign_withdraws_before_end_time() {
+        let config = config(false);
+        let mut campaign = campaign();
+        let mut backer = contribution();
+
+        // Not funded yet
+        let err = take_success_payout(&mut campaign).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::CampaignNotSuccessful.into()));
+
+        // Contribute past the 1_000 lamport goal at t = 50, before end_time
+        validate_contribution(&config, &campaign, 1_200, 50).unwrap();
+        apply_contribution(&mut campaign, &mut backer, 1_200).unwrap();
+        assert!(campaign.is_successful);
+        assert!(campaign.is_active);
+
+        assert_eq!(take_success_payout(&mut campaign).unwrap(), 1_200);
+        assert_eq!(campaign.raised_amount, 0);
+        assert!(!campaign.is_active);
+
+        let err = take_success_payout(&mut campaign).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::CampaignNotActive.into()));
+    }
+    // A campaign that ended without reaching its goal
+    fn failed_campaign() -> CampaignAccount {
+        let mut campaign = campaign();
+        campaign.is_active = false;
+        campaign
+    }
+
+    #[test]
+    fn refunded_campaign_sweeps_five_lamports_and_closes() {
+        let mut campaign = failed_campaign();
+        // Five stray lamports above rent once every backer is refunded
+        assert_eq!(take_dust(&mut campaign, 5).unwrap(), 5);
+        assert!(validate_close(&campaign).is_ok());
+    }
+
+    #[test]
+    fn successful_campaign_sweeps_raised_funds_and_dust() {
+        let mut campaign = failed_campaign();
+        campaign.is_successful = true;
+        campaign.raised_amount = 1_000;
+        campaign.contributor_count = 3;
+
+        assert_eq!(take_dust(&mut campaign, 1_005).unwrap(), 1_005);
+        assert_eq!(campaign.raised_amount, 0);
+        assert!(validate_close(&campaign).is_ok());
+    }
+
+    #[test]
+    fn outstanding_refunds_are_not_swept() {
+        let mut campaign = failed_campaign();
+        campaign.raised_amount = 100;
+        campaign.contributor_count = 1;
+
+        assert_eq!(take_dust(&mut campaign, 105).unwrap(), 5);
+        assert_eq!(campaign.raised_amount, 100);
+        let err = validate_close(&campaign).unwrap_err();
+        assert_eq!(error_code(err), error_code(CampaignError::FundsRemaining.into()));
+    }
+
+    #[test]
+    fn match_funds_survive_the_sweep_until_reclaimed() {
+        // Every backer refunded; 60 matched lamports and a 40 lamport pool remain
+        let mut campaign = failed_campaign();
+        campaign.raised_amount = 60;
+        campaign.matched_total = 60;
+        campaign.match_pool_remaining = 40;
+
+        assert_eq!(take_dust(&mut campaign, 105).unwrap(), 5);
+        assert_eq!(campaign.raised_amount, 60);
+        assert!(validate_close(&campaign).is_err());
+
+        // sweep -> reclaim -> close
+        assert_eq!(take_match_refund(&mut campaign).unwrap(), 100);
+        assert!(validate_close(&campaign).is_ok());
+    }
+}
@@ -1,16 +1,225 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import * as fs from "fs";
+import * as path from "path";
 import { Deploy } from "../target/types/deploy";
 
+const { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } = anchor.web3;
+
+// Loaded at this address through [[test.genesis]] in Anchor.toml; the
+// before hook initialises it and allows the deploy program as a caller.
+const REGISTRY_PROGRAM_ID = new PublicKey(
+  "BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn"
+);
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
 describe("deploy", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.Deploy as Program<Deploy>;
+  const registry = new Program(
+    JSON.parse(
+      fs.readFileSync(
+        path.resolve(__dirname, "../../back/test_scripts/idl/registry.json"),
+        "utf8"
+      )
+    ) as anchor.Idl,
+    provider
+  );
+  const sender = provider.wallet.publicKey;
+
+  const registryPda = (seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, REGISTRY_PROGRAM_ID)[0];
+
+  before(async () => {
+    const registryAuthority = registryPda([Buffer.from("authority")]);
+    const authority = await registry.account.authority.fetchNullable(
+      registryAuthority
+    );
+
+    // The genesis program's upgrade authority is the provider wallet
+    if (!authority) {
+      await registry.methods
+        .initRegistry()
+        .accountsPartial({
+          admin: sender,
+          program: REGISTRY_PROGRAM_ID,
+          programData: PublicKey.findProgramAddressSync(
+            [REGISTRY_PROGRAM_ID.toBuffer()],
+            BPF_LOADER_UPGRADEABLE_ID
+          )[0],
+          authority: registryAuthority,
+          config: registryPda([Buffer.from("config")]),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    const allowed = (authority?.allowedPrograms ??
+      []) as anchor.web3.PublicKey[];
+    if (!allowed.some((id) => id.equals(program.programId))) {
+      await registry.methods
+        .addAllowedProgram(program.programId)
+        .accountsPartial({ admin: sender, authority: registryAuthority })
+        .rpc();
+    }
+  });
+
+  // Derives the registry accounts for the next record registered by payer
+  const registryAccounts = async (
+    payer: anchor.web3.PublicKey,
+    initiator: anchor.web3.PublicKey
+  ) => {
+    const registryCounter = registryPda([
+      Buffer.from("counter"),
+      payer.toBuffer(),
+    ]);
+    const counterInfo = await provider.connection.getAccountInfo(
+      registryCounter
+    );
+    // Skip the 8 byte account discriminator to read the u64 count
+    const sequence = counterInfo
+      ? counterInfo.data.subarray(8, 16)
+      : Buffer.alloc(8);
+
+    const registryConfig = registryPda([Buffer.from("config")]);
+    const configInfo = await provider.connection.getAccountInfo(
+      registryConfig
+    );
+    // The treasury follows the discriminator and the u64 fee
+    const registryTreasury = new PublicKey(configInfo.data.subarray(16, 48));
+
+    return {
+      registryProgram: REGISTRY_PROGRAM_ID,
+      registryAuthority: registryPda([Buffer.from("authority")]),
+      registryCounter,
+      registryTransaction: registryPda([
+        Buffer.from("transaction_v1"),
+        payer.toBuffer(),
+        Buffer.from(sequence),
+      ]),
+      registryInitiatorStats: registryPda([
+        Buffer.from("stats"),
+        initiator.toBuffer(),
+      ]),
+      registryConfig,
+      registryTreasury,
+      callerProgram: program.programId,
+      registryCaller: PublicKey.findProgramAddressSync(
+        [Buffer.from("registry_caller")],
+        program.programId
+      )[0],
+    };
+  };
+
+  const initializeEscrow = async (amount: anchor.BN) => {
+    const receiver = Keypair.generate();
+    const escrowAccount = Keypair.generate();
+
+    await program.methods
+      .initialize(amount, "manual release")
+      .accountsPartial({
+        sender,
+        receiver: receiver.publicKey,
+        escrowAuthority: sender,
+        escrowAccount: escrowAccount.publicKey,
+        systemProgram: SystemProgram.programId,
+        ...(await registryAccounts(sender, sender)),
+      })
+      .signers([escrowAccount])
+      .rpc();
+
+    return { receiver: receiver.publicKey, escrowAccount: escrowAccount.publicKey };
+  };
+
+  const release = async (
+    escrowAccount: anchor.web3.PublicKey,
+    receiver: anchor.web3.PublicKey
+  ) =>
+    program.methods
+      .release()
+      .accountsPartial({
+        escrowAuthority: sender,
+        escrowAccount,
+        receiver,
+        systemProgram: SystemProgram.programId,
+        ...(await registryAccounts(sender, sender)),
+      })
+      .rpc();
+
+  it("releases escrowed funds to the receiver", async () => {
+    const amount = new anchor.BN(LAMPORTS_PER_SOL / 10);
+    const { receiver, escrowAccount } = await initializeEscrow(amount);
+
+    const receiverBefore = await provider.connection.getBalance(receiver);
+
+    await release(escrowAccount, receiver);
+
+    const receiverAfter = await provider.connection.getBalance(receiver);
+    expect(receiverAfter - receiverBefore).to.equal(amount.toNumber());
+
+    const escrow = await program.account.escrowAccount.fetch(escrowAccount);
+    expect(escrow.isCompleted).to.be.true;
+  });
+
+  it("rejects closing an escrow that is not completed", async () => {
+    const { escrowAccount } = await initializeEscrow(new anchor.BN(1_000_000));
+
+    try {
+      await program.methods
+        .close()
+        .accountsPartial({ sender, escrowAccount })
+        .rpc();
+      expect.fail("close should have been rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("EscrowStillActive");
+    }
+  });
+
+  it("closes a completed escrow and returns the rent", async () => {
+    const { receiver, escrowAccount } = await initializeEscrow(
+      new anchor.BN(1_000_000)
+    );
+    await release(escrowAccount, receiver);
+
+    await program.methods
+      .close()
+      .accountsPartial({ sender, escrowAccount })
+      .rpc();
+
+    const closed = await provider.connection.getAccountInfo(escrowAccount);
+    expect(closed).to.be.null;
+  });
+
+  it("registers a transaction through the signed registry CPI", async () => {
+    const target = Keypair.generate().publicKey;
+    const accounts = await registryAccounts(sender, sender);
+
+    await program.methods
+      .registerTransaction({
+        txType: "Manual",
+        amount: new anchor.BN(42),
+        initiator: sender,
+        targetAccount: target,
+        description: "Registered directly through the escrow program",
+      })
+      .accountsPartial({
+        payer: sender,
+        systemProgram: SystemProgram.programId,
+        ...accounts,
+      })
+      .rpc();
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+    const record = await registry.account.transactionRecord.fetch(
+      accounts.registryTransaction
+    );
+    expect(record.amount.toNumber()).to.equal(42);
+    expect(record.targetAccount.equals(target)).to.be.true;
+    expect(record.callerProgramId.equals(program.programId)).to.be.true;
   });
 });
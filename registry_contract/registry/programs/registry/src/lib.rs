@@ -1,12 +1,78 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 // Fixed Program ID for the Registry contract - this is the actual deployed ID
 declare_id!("BhETt1LhzVYpK5DTcRuNZdKyb3QTz8HktUoXQJQapmvn");
 
+// Seed of the PDA a caller program signs registrations with
+pub const REGISTRY_CALLER_SEED: &[u8] = b"registry_caller";
+
 #[program]
 pub mod registry {
     use super::*;
     
+    pub fn init_registry(ctx: Context<InitRegistry>) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        
+        authority.admin = ctx.accounts.admin.key();
+        authority.allowed_programs = Vec::new();
+        authority.bump = ctx.bumps.authority;
+        
+        // Registrations are free until the admin sets a fee
+        let config = &mut ctx.accounts.config;
+        config.fee_lamports = 0;
+        config.treasury = authority.admin;
+        config.bump = ctx.bumps.config;
+        
+        msg!("Registry initialised with admin {}", authority.admin);
+        
+        Ok(())
+    }
+    
+    pub fn add_allowed_program(ctx: Context<UpdateAllowedPrograms>, program_id: Pubkey) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        
+        require!(
+            !authority.allowed_programs.contains(&program_id),
+            RegistryError::ProgramAlreadyAllowed
+        );
+        require!(
+            authority.allowed_programs.len() < Authority::MAX_ALLOWED_PROGRAMS,
+            RegistryError::AllowedProgramsFull
+        );
+        
+        authority.allowed_programs.push(program_id);
+        
+        msg!("Allowed caller program added: {}", program_id);
+        
+        Ok(())
+    }
+    
+    pub fn remove_allowed_program(ctx: Context<UpdateAllowedPrograms>, program_id: Pubkey) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        
+        let index = authority.allowed_programs
+            .iter()
+            .position(|allowed| *allowed == program_id)
+            .ok_or(RegistryError::ProgramNotAllowed)?;
+        authority.allowed_programs.remove(index);
+        
+        msg!("Allowed caller program removed: {}", program_id);
+        
+        Ok(())
+    }
+    
+    pub fn set_fee(ctx: Context<SetFee>, fee_lamports: u64, treasury: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        config.fee_lamports = fee_lamports;
+        config.treasury = treasury;
+        
+        msg!("Registration fee set to {} lamports", fee_lamports);
+        
+        Ok(())
+    }
+    
     pub fn register_transaction(
         ctx: Context<RegisterTransaction>,
         tx_type: String,
@@ -15,7 +81,35 @@ pub mod registry {
         target_account: Pubkey,
         description: String,
     ) -> Result<()> {
+        // Reject strings that would not fit the record's reserved space
+        require!(
+            tx_type.len() <= TransactionRecord::MAX_TX_TYPE_LEN,
+            RegistryError::TxTypeTooLong
+        );
+        require!(
+            description.len() <= TransactionRecord::MAX_DESCRIPTION_LEN,
+            RegistryError::DescriptionTooLong
+        );
+        
+        // Collect the registration fee before writing anything
+        let fee_lamports = ctx.accounts.config.fee_lamports;
+        if fee_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                fee_lamports,
+            )
+            .map_err(|_| RegistryError::FeePaymentFailed)?;
+        }
+        
+        let transaction_counter = &mut ctx.accounts.transaction_counter;
         let transaction_record = &mut ctx.accounts.transaction_record;
+        let initiator_stats = &mut ctx.accounts.initiator_stats;
         
         // Store transaction data
         transaction_record.tx_type = tx_type;
@@ -25,12 +119,47 @@ pub mod registry {
         transaction_record.caller_program_id = ctx.accounts.caller_program_id.key();
         transaction_record.description = description;
         transaction_record.timestamp = Clock::get()?.unix_timestamp;
+        transaction_record.sequence = transaction_counter.count;
+        transaction_record.payer = ctx.accounts.payer.key();
+        transaction_record.archived = false;
+        
+        // Advance the payer's sequence so the next record gets a fresh PDA
+        transaction_counter.count = transaction_counter.count
+            .checked_add(1)
+            .ok_or(RegistryError::CounterOverflow)?;
+        
+        // Aggregate the initiator's registrations
+        initiator_stats.initiator = initiator;
+        initiator_stats.bump = ctx.bumps.initiator_stats;
+        initiator_stats.count = initiator_stats.count
+            .checked_add(1)
+            .ok_or(RegistryError::CounterOverflow)?;
+        initiator_stats.total_amount = initiator_stats.total_amount
+            .checked_add(amount)
+            .ok_or(RegistryError::AmountOverflow)?;
         
         msg!("Transaction registered: {} SOL", amount as f64 / 1_000_000_000.0);
         
         Ok(())
     }
     
+    pub fn archive_transaction(ctx: Context<ArchiveTransaction>) -> Result<()> {
+        let transaction_record = &mut ctx.accounts.transaction_record;
+        
+        require!(!transaction_record.archived, RegistryError::AlreadyArchived);
+        transaction_record.archived = true;
+        
+        emit!(TransactionArchived {
+            transaction_record: transaction_record.key(),
+            payer: transaction_record.payer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Transaction archived");
+        
+        Ok(())
+    }
+    
     pub fn verify_transaction(
         ctx: Context<VerifyTransaction>,
         expected_amount: u64,
@@ -43,20 +172,137 @@ pub mod registry {
             RegistryError::InvalidAmount
         );
         
+        emit!(TransactionVerified {
+            transaction_record: ctx.accounts.transaction_record.key(),
+            requester: ctx.accounts.requester.key(),
+            expected_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Transaction verified successfully");
         
         Ok(())
     }
+    
+    pub fn verify_with_details(
+        ctx: Context<VerifyTransaction>,
+        expected_amount: u64,
+        expected_initiator: Pubkey,
+    ) -> Result<()> {
+        let transaction_record = &ctx.accounts.transaction_record;
+        
+        // Check both the amount and the initiator of the transaction
+        require!(
+            transaction_record.amount == expected_amount,
+            RegistryError::InvalidAmount
+        );
+        require!(
+            transaction_record.initiator == expected_initiator,
+            RegistryError::InitiatorMismatch
+        );
+        
+        emit!(TransactionVerified {
+            transaction_record: ctx.accounts.transaction_record.key(),
+            requester: ctx.accounts.requester.key(),
+            expected_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Transaction verified successfully with details");
+        
+        Ok(())
+    }
+    
+    // Strict variants treat archived records as no longer valid; the
+    // lenient instructions above keep accepting them
+    pub fn verify_transaction_strict(
+        ctx: Context<VerifyTransaction>,
+        expected_amount: u64,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.transaction_record.archived,
+            RegistryError::TransactionArchived
+        );
+        verify_transaction(ctx, expected_amount)
+    }
+    
+    pub fn verify_with_details_strict(
+        ctx: Context<VerifyTransaction>,
+        expected_amount: u64,
+        expected_initiator: Pubkey,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.transaction_record.archived,
+            RegistryError::TransactionArchived
+        );
+        verify_with_details(ctx, expected_amount, expected_initiator)
+    }
+    
+    pub fn verify_initiator_total(
+        ctx: Context<VerifyInitiatorTotal>,
+        expected_total: u64,
+    ) -> Result<()> {
+        let initiator_stats = &ctx.accounts.initiator_stats;
+        
+        // Check that the initiator's aggregated volume matches
+        require!(
+            initiator_stats.total_amount == expected_total,
+            RegistryError::InvalidTotal
+        );
+        
+        msg!("Initiator total verified successfully");
+        
+        Ok(())
+    }
+    
+    pub fn describe_schema(_ctx: Context<DescribeSchema>) -> Result<()> {
+        // Publish the record layout so clients can validate inputs up front
+        emit!(SchemaDescribed {
+            schema_version: TransactionRecord::SCHEMA_VERSION,
+            max_tx_type_len: TransactionRecord::MAX_TX_TYPE_LEN as u32,
+            max_description_len: TransactionRecord::MAX_DESCRIPTION_LEN as u32,
+            record_size: TransactionRecord::SIZE as u64,
+        });
+        
+        msg!("Registry schema version {}", TransactionRecord::SCHEMA_VERSION);
+        
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
-#[instruction(tx_type: String, amount: u64)]
+#[instruction(tx_type: String, amount: u64, initiator: Pubkey)]
 pub struct RegisterTransaction<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    /// CHECK: Recorded as the caller; caller_authority proves it invoked us
     pub caller_program_id: AccountInfo<'info>,
     
+    // Only the caller program itself can sign for this PDA, via invoke_signed
+    #[account(
+        seeds = [REGISTRY_CALLER_SEED],
+        bump,
+        seeds::program = caller_program_id.key()
+    )]
+    pub caller_authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"authority"],
+        bump = authority.bump,
+        constraint = authority.allowed_programs.contains(&caller_program_id.key()) @ RegistryError::UnauthorizedCaller
+    )]
+    pub authority: Account<'info, Authority>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + TransactionCounter::SIZE,
+        seeds = [b"counter", payer.key().as_ref()],
+        bump
+    )]
+    pub transaction_counter: Account<'info, TransactionCounter>,
+    
     #[account(
         init,
         payer = payer,
@@ -64,16 +310,115 @@ pub struct RegisterTransaction<'info> {
         seeds = [
             b"transaction_v1",
             payer.key().as_ref(),
-            tx_type.as_bytes(),
-            &amount.to_le_bytes()
+            &transaction_counter.count.to_le_bytes()
         ],
         bump
     )]
     pub transaction_record: Account<'info, TransactionRecord>,
     
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + InitiatorStats::SIZE,
+        seeds = [b"stats", initiator.as_ref()],
+        bump
+    )]
+    pub initiator_stats: Account<'info, InitiatorStats>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// CHECK: Fee recipient, must match the treasury stored on the config
+    #[account(
+        mut,
+        address = config.treasury @ RegistryError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+    
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct InitRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Registry>,
+    
+    // Only the upgrade authority can claim the admin role
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ RegistryError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Authority::SIZE,
+        seeds = [b"authority"],
+        bump
+    )]
+    pub authority: Account<'info, Authority>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAllowedPrograms<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"authority"],
+        bump = authority.bump,
+        has_one = admin @ RegistryError::Unauthorized
+    )]
+    pub authority: Account<'info, Authority>,
+}
+
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(
+        seeds = [b"authority"],
+        bump = authority.bump,
+        has_one = admin @ RegistryError::Unauthorized
+    )]
+    pub authority: Account<'info, Authority>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveTransaction<'info> {
+    pub payer: Signer<'info>,
+    
+    #[account(
+        mut,
+        has_one = payer @ RegistryError::Unauthorized
+    )]
+    pub transaction_record: Account<'info, TransactionRecord>,
+}
+
 #[derive(Accounts)]
 pub struct VerifyTransaction<'info> {
     pub requester: Signer<'info>,
@@ -81,6 +426,22 @@ pub struct VerifyTransaction<'info> {
     pub transaction_record: Account<'info, TransactionRecord>,
 }
 
+#[derive(Accounts)]
+pub struct VerifyInitiatorTotal<'info> {
+    pub requester: Signer<'info>,
+    
+    #[account(
+        seeds = [b"stats", initiator_stats.initiator.as_ref()],
+        bump = initiator_stats.bump
+    )]
+    pub initiator_stats: Account<'info, InitiatorStats>,
+}
+
+#[derive(Accounts)]
+pub struct DescribeSchema<'info> {
+    pub requester: Signer<'info>,
+}
+
 #[account]
 pub struct TransactionRecord {
     pub tx_type: String,        // Max 50 chars: 50 * 4 = 200
@@ -90,15 +451,119 @@ pub struct TransactionRecord {
     pub caller_program_id: Pubkey, // 32 bytes
     pub description: String,    // Max 100 chars: 100 * 4 = 400
     pub timestamp: i64,         // 8 bytes
+    pub sequence: u64,          // 8 bytes
+    pub payer: Pubkey,          // 32 bytes
+    pub archived: bool,         // 1 byte
 }
 
 // Calculate total size
 impl TransactionRecord {
-    pub const SIZE: usize = 200 + 8 + 32 + 32 + 32 + 400 + 8;
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const MAX_TX_TYPE_LEN: usize = 50;
+    pub const MAX_DESCRIPTION_LEN: usize = 100;
+    pub const SIZE: usize = 200 + 8 + 32 + 32 + 32 + 400 + 8 + 8 + 32 + 1;
+}
+
+#[account]
+pub struct Authority {
+    pub admin: Pubkey,                  // 32 bytes
+    pub allowed_programs: Vec<Pubkey>,  // 4 + 32 * MAX_ALLOWED_PROGRAMS
+    pub bump: u8,                       // 1 byte
+}
+
+impl Authority {
+    pub const MAX_ALLOWED_PROGRAMS: usize = 10;
+    pub const SIZE: usize = 32 + 4 + 32 * Self::MAX_ALLOWED_PROGRAMS + 1;
+}
+
+#[account]
+pub struct Config {
+    pub fee_lamports: u64,              // 8 bytes
+    pub treasury: Pubkey,               // 32 bytes
+    pub bump: u8,                       // 1 byte
+}
+
+impl Config {
+    pub const SIZE: usize = 8 + 32 + 1;
+}
+
+#[account]
+pub struct TransactionCounter {
+    pub count: u64,             // 8 bytes
+}
+
+impl TransactionCounter {
+    pub const SIZE: usize = 8;
+}
+
+#[account]
+pub struct InitiatorStats {
+    pub initiator: Pubkey,      // 32 bytes
+    pub count: u64,             // 8 bytes
+    pub total_amount: u64,      // 8 bytes
+    pub bump: u8,               // 1 byte
+}
+
+impl InitiatorStats {
+    pub const SIZE: usize = 32 + 8 + 8 + 1;
 }
 
 #[error_code]
 pub enum RegistryError {
     #[msg("Transaction amount does not match expected amount")]
     InvalidAmount,
+    #[msg("Transaction counter overflow")]
+    CounterOverflow,
+    #[msg("Caller program is not allowed to register transactions")]
+    UnauthorizedCaller,
+    #[msg("Only the registry admin can perform this action")]
+    Unauthorized,
+    #[msg("Program is already on the allowed list")]
+    ProgramAlreadyAllowed,
+    #[msg("Program is not on the allowed list")]
+    ProgramNotAllowed,
+    #[msg("Allowed program list is full")]
+    AllowedProgramsFull,
+    #[msg("Transaction amount overflow")]
+    AmountOverflow,
+    #[msg("Initiator total does not match expected total")]
+    InvalidTotal,
+    #[msg("Transaction initiator does not match expected initiator")]
+    InitiatorMismatch,
+    #[msg("Transaction has been archived")]
+    TransactionArchived,
+    #[msg("Transaction is already archived")]
+    AlreadyArchived,
+    #[msg("Transaction type exceeds 50 characters")]
+    TxTypeTooLong,
+    #[msg("Description exceeds 100 characters")]
+    DescriptionTooLong,
+    #[msg("Registration fee could not be paid")]
+    FeePaymentFailed,
+    #[msg("Treasury does not match the registry config")]
+    InvalidTreasury,
+}
+
+// Events
+#[event]
+pub struct TransactionVerified {
+    pub transaction_record: Pubkey,
+    pub requester: Pubkey,
+    pub expected_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransactionArchived {
+    pub transaction_record: Pubkey,
+    pub payer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SchemaDescribed {
+    pub schema_version: u8,
+    pub max_tx_type_len: u32,
+    pub max_description_len: u32,
+    pub record_size: u64,
 }
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use registry::cpi::accounts::RegisterTransaction;
+use registry::program::Registry;
+use registry::REGISTRY_CALLER_SEED;
+
+// Test-only program: registers arbitrary transactions with the registry so
+// its tests can exercise a real CPI caller. Never add it to a deployed
+// registry's allowed caller list.
+declare_id!("DdKShfKG3LnvLHv6ioBrQx62WSvQxjv2YjbgKmbtp1fm");
+
+#[program]
+pub mod registry_caller {
+    use super::*;
+    
+    pub fn register(
+        ctx: Context<Register>,
+        tx_type: String,
+        amount: u64,
+        initiator: Pubkey,
+        target_account: Pubkey,
+        description: String,
+    ) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[REGISTRY_CALLER_SEED, &[ctx.bumps.caller_authority]]];
+        
+        registry::cpi::register_transaction(
+            CpiContext::new_with_signer(
+                ctx.accounts.registry_program.to_account_info(),
+                RegisterTransaction {
+                    payer: ctx.accounts.payer.to_account_info(),
+                    caller_program_id: ctx.accounts.caller_program.to_account_info(),
+                    caller_authority: ctx.accounts.caller_authority.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                    transaction_counter: ctx.accounts.transaction_counter.to_account_info(),
+                    transaction_record: ctx.accounts.transaction_record.to_account_info(),
+                    initiator_stats: ctx.accounts.initiator_stats.to_account_info(),
+                    config: ctx.accounts.config.to_account_info(),
+                    treasury: ctx.accounts.treasury.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            tx_type,
+            amount,
+            initiator,
+            target_account,
+            description,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Register<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: This program, recorded by the registry as the caller
+    #[account(address = crate::ID)]
+    pub caller_program: AccountInfo<'info>,
+    
+    /// CHECK: PDA that signs the registry CPI on behalf of this program
+    #[account(seeds = [REGISTRY_CALLER_SEED], bump)]
+    pub caller_authority: AccountInfo<'info>,
+    
+    pub registry_program: Program<'info, Registry>,
+    
+    /// CHECK: Registry Authority PDA, verified by the Registry program
+    pub authority: AccountInfo<'info>,
+    
+    /// CHECK: Per-payer TransactionCounter PDA, managed by the Registry program
+    #[account(mut)]
+    pub transaction_counter: AccountInfo<'info>,
+    
+    /// CHECK: TransactionRecord PDA, initialised by the Registry program
+    #[account(mut)]
+    pub transaction_record: AccountInfo<'info>,
+    
+    /// CHECK: Per-initiator InitiatorStats PDA, managed by the Registry program
+    #[account(mut)]
+    pub initiator_stats: AccountInfo<'info>,
+    
+    /// CHECK: Registry Config PDA, verified by the Registry program
+    pub config: AccountInfo<'info>,
+    
+    /// CHECK: Fee treasury, verified against the config by the Registry program
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -1,16 +1,463 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
 import { Registry } from "../target/types/registry";
+import { RegistryCaller } from "../target/types/registry_caller";
+
+const { LAMPORTS_PER_SOL, PublicKey, SystemProgram } = anchor.web3;
+
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
 
 describe("registry", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.registry as Program<Registry>;
+  // Test-only program that registers through a signed CPI
+  const caller = anchor.workspace.registryCaller as Program<RegistryCaller>;
+  const payer = provider.wallet.publicKey;
+  const otherWallet = anchor.web3.Keypair.generate().publicKey;
+
+  const callerAuthorityPda = PublicKey.findProgramAddressSync(
+    [Buffer.from("registry_caller")],
+    caller.programId
+  )[0];
+
+  const authorityPda = PublicKey.findProgramAddressSync(
+    [Buffer.from("authority")],
+    program.programId
+  )[0];
+
+  const configPda = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  )[0];
+
+  const counterPda = () =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("counter"), payer.toBuffer()],
+      program.programId
+    )[0];
+
+  const recordPda = (sequence: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("transaction_v1"),
+        payer.toBuffer(),
+        sequence.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const statsPda = (initiator: anchor.web3.PublicKey = payer) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), initiator.toBuffer()],
+      program.programId
+    )[0];
+
+  const nextSequence = async () => {
+    const counter = await program.account.transactionCounter.fetchNullable(
+      counterPda()
+    );
+    return counter ? counter.count : new anchor.BN(0);
+  };
+
+  const register = async (
+    amount: anchor.BN,
+    txType = "escrow_initialize",
+    description = "Registry test transaction",
+    initiator = payer
+  ) => {
+    const sequence = await nextSequence();
+    const record = recordPda(sequence);
+    const config = await program.account.config.fetch(configPda);
+    await caller.methods
+      .register(txType, amount, initiator, payer, description)
+      .accountsPartial({
+        payer,
+        callerProgram: caller.programId,
+        callerAuthority: callerAuthorityPda,
+        registryProgram: program.programId,
+        authority: authorityPda,
+        transactionCounter: counterPda(),
+        transactionRecord: record,
+        initiatorStats: statsPda(initiator),
+        config: configPda,
+        treasury: config.treasury,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    return { sequence, record };
+  };
+
+  before(async () => {
+    const authority = await program.account.authority.fetchNullable(
+      authorityPda
+    );
+    if (!authority) {
+      await program.methods
+        .initRegistry()
+        .accountsPartial({
+          admin: payer,
+          program: program.programId,
+          programData: PublicKey.findProgramAddressSync(
+            [program.programId.toBuffer()],
+            BPF_LOADER_UPGRADEABLE_ID
+          )[0],
+          authority: authorityPda,
+          config: configPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    await setCallerAllowed(true);
+  });
+
+  const setCallerAllowed = (allowed: boolean) =>
+    (allowed
+      ? program.methods.addAllowedProgram(caller.programId)
+      : program.methods.removeAllowedProgram(caller.programId)
+    )
+      .accountsPartial({ admin: payer, authority: authorityPda })
+      .rpc();
+
+  it("registers transactions from an allowed caller", async () => {
+    const { record } = await register(new anchor.BN(500));
+
+    const stored = await program.account.transactionRecord.fetch(record);
+    expect(stored.callerProgramId.toBase58()).to.equal(
+      caller.programId.toBase58()
+    );
+  });
+
+  it("rejects transactions from a caller that is not allowed", async () => {
+    await setCallerAllowed(false);
+    try {
+      await register(new anchor.BN(500));
+      expect.fail("registration should have been rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("UnauthorizedCaller");
+    } finally {
+      await setCallerAllowed(true);
+    }
+  });
+
+  it("rejects a caller that does not sign as the claimed program", async () => {
+    // A wallet can name an allowed program but cannot sign for its PDA
+    const impostor = anchor.web3.Keypair.generate();
+    const sequence = await nextSequence();
+    const config = await program.account.config.fetch(configPda);
+    try {
+      await program.methods
+        .registerTransaction(
+          "escrow_initialize",
+          new anchor.BN(500),
+          payer,
+          payer,
+          "Spoofed"
+        )
+        .accountsPartial({
+          payer,
+          callerProgramId: caller.programId,
+          callerAuthority: impostor.publicKey,
+          authority: authorityPda,
+          transactionCounter: counterPda(),
+          transactionRecord: recordPda(sequence),
+          initiatorStats: statsPda(),
+          config: configPda,
+          treasury: config.treasury,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([impostor])
+        .rpc();
+      expect.fail("registration should have been rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+    }
+  });
+
+  it("registers identical transactions under distinct PDAs", async () => {
+    const amount = new anchor.BN(1_000_000);
+
+    const first = await register(amount);
+    const second = await register(amount);
+
+    expect(first.record.toBase58()).to.not.equal(second.record.toBase58());
+
+    const firstRecord = await program.account.transactionRecord.fetch(
+      first.record
+    );
+    const secondRecord = await program.account.transactionRecord.fetch(
+      second.record
+    );
+    expect(firstRecord.sequence.toNumber()).to.equal(
+      first.sequence.toNumber()
+    );
+    expect(secondRecord.sequence.toNumber()).to.equal(
+      first.sequence.toNumber() + 1
+    );
+  });
+
+  describe("string limits", () => {
+    const amount = new anchor.BN(100);
+
+    it("accepts a tx_type and description at the limits", async () => {
+      const { record } = await register(
+        amount,
+        "t".repeat(50),
+        "d".repeat(100)
+      );
+
+      const stored = await program.account.transactionRecord.fetch(record);
+      expect(stored.txType).to.have.lengthOf(50);
+      expect(stored.description).to.have.lengthOf(100);
+    });
+
+    it("rejects a tx_type over 50 characters", async () => {
+      try {
+        await register(amount, "t".repeat(51));
+        expect.fail("registration should have been rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("TxTypeTooLong");
+      }
+    });
+
+    it("rejects a description over 100 characters", async () => {
+      try {
+        await register(amount, "escrow_initialize", "d".repeat(101));
+        expect.fail("registration should have been rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("DescriptionTooLong");
+      }
+    });
+  });
+
+  describe("verification", () => {
+    const amount = new anchor.BN(2_000);
+    let record: anchor.web3.PublicKey;
+
+    before(async () => {
+      ({ record } = await register(amount));
+    });
+
+    const verifyWithDetails = (
+      expectedAmount: anchor.BN,
+      expectedInitiator: anchor.web3.PublicKey
+    ) =>
+      program.methods
+        .verifyWithDetails(expectedAmount, expectedInitiator)
+        .accountsPartial({ requester: payer, transactionRecord: record })
+        .rpc();
+
+    it("verifies a matching amount and initiator", async () => {
+      await verifyWithDetails(amount, payer);
+    });
+
+    it("rejects a mismatched amount", async () => {
+      try {
+        await verifyWithDetails(amount.addn(1), payer);
+        expect.fail("verification should have failed");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidAmount");
+      }
+    });
+
+    it("rejects a mismatched initiator", async () => {
+      try {
+        await verifyWithDetails(amount, otherWallet);
+        expect.fail("verification should have failed");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InitiatorMismatch");
+      }
+    });
+  });
+
+  describe("initiator stats", () => {
+    // A fresh initiator so earlier registrations do not affect the totals
+    const initiator = anchor.web3.Keypair.generate().publicKey;
+
+    const verifyInitiatorTotal = (expectedTotal: anchor.BN) =>
+      program.methods
+        .verifyInitiatorTotal(expectedTotal)
+        .accountsPartial({
+          requester: payer,
+          initiatorStats: statsPda(initiator),
+        })
+        .rpc();
+
+    before(async () => {
+      // The first registration creates the stats account
+      expect(
+        await program.account.initiatorStats.fetchNullable(
+          statsPda(initiator)
+        )
+      ).to.be.null;
+      await register(
+        new anchor.BN(700),
+        "escrow_initialize",
+        "Registry test transaction",
+        initiator
+      );
+      await register(
+        new anchor.BN(300),
+        "escrow_release",
+        "Registry test transaction",
+        initiator
+      );
+    });
+
+    it("aggregates count and volume per initiator", async () => {
+      const stats = await program.account.initiatorStats.fetch(
+        statsPda(initiator)
+      );
+      expect(stats.initiator.equals(initiator)).to.be.true;
+      expect(stats.count.toNumber()).to.equal(2);
+      expect(stats.totalAmount.toNumber()).to.equal(1_000);
+    });
+
+    it("verifies the correct initiator total", async () => {
+      await verifyInitiatorTotal(new anchor.BN(1_000));
+    });
+
+    it("rejects a wrong initiator total", async () => {
+      try {
+        await verifyInitiatorTotal(new anchor.BN(999));
+        expect.fail("verification should have failed");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidTotal");
+      }
+    });
+  });
+
+  describe("schema", () => {
+    it("describes the transaction record layout", async () => {
+      let listener: number;
+      const described = new Promise<any>((resolve) => {
+        listener = program.addEventListener("schemaDescribed", resolve);
+      });
+
+      await program.methods
+        .describeSchema()
+        .accountsPartial({ requester: payer })
+        .rpc();
+
+      const schema = await described;
+      await program.removeEventListener(listener);
+
+      expect(schema.schemaVersion).to.equal(1);
+      expect(schema.maxTxTypeLen).to.equal(50);
+      expect(schema.maxDescriptionLen).to.equal(100);
+      // 200 + 8 + 32 + 32 + 32 + 400 + 8 + 8 + 32 + 1, excluding the discriminator
+      expect(schema.recordSize.toNumber()).to.equal(753);
+    });
+  });
+
+  describe("fees", () => {
+    const treasury = anchor.web3.Keypair.generate().publicKey;
+    // Large enough to leave the new treasury account rent-exempt
+    const fee = new anchor.BN(LAMPORTS_PER_SOL / 100);
+
+    const setFee = (feeLamports: anchor.BN) =>
+      program.methods
+        .setFee(feeLamports, treasury)
+        .accountsPartial({ admin: payer, authority: authorityPda, config: configPda })
+        .rpc();
+
+    after(async () => {
+      await setFee(new anchor.BN(0));
+    });
+
+    it("charges the fee to the treasury when set", async () => {
+      await setFee(fee);
+
+      await register(new anchor.BN(100));
+
+      const balance = await provider.connection.getBalance(treasury);
+      expect(balance).to.equal(fee.toNumber());
+    });
+
+    it("does not charge when the fee is zero", async () => {
+      await setFee(new anchor.BN(0));
+      const before = await provider.connection.getBalance(treasury);
+
+      await register(new anchor.BN(100));
+
+      const after = await provider.connection.getBalance(treasury);
+      expect(after).to.equal(before);
+    });
+
+    it("rejects fee changes from a non-admin", async () => {
+      const outsider = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .setFee(fee, treasury)
+          .accountsPartial({
+            admin: outsider.publicKey,
+            authority: authorityPda,
+            config: configPda,
+          })
+          .signers([outsider])
+          .rpc();
+        expect.fail("set_fee should have been rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("Unauthorized");
+      }
+    });
+  });
+
+  describe("archiving", () => {
+    it("archives a record so strict verification fails", async () => {
+      const amount = new anchor.BN(3_000);
+      const { record } = await register(amount);
+
+      // Strict verification accepts the record until it is archived
+      await program.methods
+        .verifyTransactionStrict(amount)
+        .accountsPartial({ requester: payer, transactionRecord: record })
+        .rpc();
+
+      await program.methods
+        .archiveTransaction()
+        .accountsPartial({ payer, transactionRecord: record })
+        .rpc();
+
+      const stored = await program.account.transactionRecord.fetch(record);
+      expect(stored.archived).to.be.true;
+
+      // Lenient verification still accepts the archived record
+      await program.methods
+        .verifyTransaction(amount)
+        .accountsPartial({ requester: payer, transactionRecord: record })
+        .rpc();
+
+      try {
+        await program.methods
+          .verifyTransactionStrict(amount)
+          .accountsPartial({ requester: payer, transactionRecord: record })
+          .rpc();
+        expect.fail("strict verification should have failed");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("TransactionArchived");
+      }
+
+      await program.methods
+        .verifyWithDetails(amount, payer)
+        .accountsPartial({ requester: payer, transactionRecord: record })
+        .rpc();
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+      try {
+        await program.methods
+          .verifyWithDetailsStrict(amount, payer)
+          .accountsPartial({ requester: payer, transactionRecord: record })
+          .rpc();
+        expect.fail("strict detailed verification should have failed");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("TransactionArchived");
+      }
+    });
   });
 });